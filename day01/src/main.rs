@@ -11,6 +11,22 @@ fn compute_fuel_from_mass_part2(mass: i32) -> i32 {
     }
 }
 
+fn main() {
+    let puzzle_input = include_str!("input.txt");
+    let modules_mass = puzzle_input
+        .lines()
+        .map(|line| line.parse::<i32>().unwrap());
+
+    let mut total_fuel_part1 = 0;
+    let mut total_fuel_part2 = 0;
+    for mass in modules_mass {
+        total_fuel_part1 += compute_fuel_from_mass_part1(mass);
+        total_fuel_part2 += compute_fuel_from_mass_part2(mass);
+    }
+    println!("part1 {}", total_fuel_part1);
+    println!("part2 {}", total_fuel_part2);
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -28,19 +44,3 @@ mod tests {
         assert_eq!(super::compute_fuel_from_mass_part2(100756), 50346);
     }
 }
-
-fn main() {
-    let puzzle_input = include_str!("input.txt");
-    let modules_mass = puzzle_input
-        .lines()
-        .map(|line| line.parse::<i32>().unwrap());
-
-    let mut total_fuel_part1 = 0;
-    let mut total_fuel_part2 = 0;
-    for mass in modules_mass {
-        total_fuel_part1 += compute_fuel_from_mass_part1(mass);
-        total_fuel_part2 += compute_fuel_from_mass_part2(mass);
-    }
-    println!("part1 {}", total_fuel_part1);
-    println!("part2 {}", total_fuel_part2);
-}
@@ -1,11 +1,20 @@
+mod parser;
+
+use std::convert::TryFrom;
 fn parse_program(puzzle_input: &str) -> Vec<usize> {
-    puzzle_input
-        .split(',')
-        .map(|line| line.parse::<usize>().unwrap())
+    let values = parser::parse_intcode(puzzle_input)
+        .unwrap_or_else(|err| panic!("invalid program: {}", err));
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            usize::try_from(*v)
+                .unwrap_or_else(|_| panic!("invalid program: value {}: {} out of range", i, v))
+        })
         .collect()
 }
 
-fn eval_program(program: &mut Vec<usize>) {
+fn eval_program(program: &mut [usize]) {
     let mut instrp = 0;
     while program[instrp] != 99 {
         let param0 = program[program[instrp + 1]];
@@ -27,18 +36,6 @@ fn parse_and_eval_program(puzzle_input: &str) -> Vec<usize> {
     program
 }
 
-
-#[cfg(test)]
-mod tests {
-    #[test]
-    fn samples_day02_part1() {
-        assert_eq!(super::parse_and_eval_program("1,0,0,0,99"), [2,0,0,0,99]);
-        assert_eq!(super::parse_and_eval_program("2,3,0,3,99"), [2,3,0,6,99]);
-        assert_eq!(super::parse_and_eval_program("2,4,4,5,99,0"), [2,4,4,5,99,9801]);
-        assert_eq!(super::parse_and_eval_program("1,1,1,4,99,5,6,0,99"), [30,1,1,4,2,5,6,0,99]);
-    }
-}
-
 fn main() {
     let puzzle_input = include_str!("input.txt");
     let original_program = parse_program(puzzle_input);
@@ -52,7 +49,7 @@ fn main() {
     }
 
     'noun: for noun in 0..100 {
-        'verb: for verb in 0..100 {
+        for verb in 0..100 {
             let mut program = original_program.clone();
             program[1] = noun;
             program[2] = verb;
@@ -64,3 +61,31 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn samples_day02_part1() {
+        assert_eq!(super::parse_and_eval_program("1,0,0,0,99"), [2,0,0,0,99]);
+        assert_eq!(super::parse_and_eval_program("2,3,0,3,99"), [2,3,0,6,99]);
+        assert_eq!(super::parse_and_eval_program("2,4,4,5,99,0"), [2,4,4,5,99,9801]);
+        assert_eq!(super::parse_and_eval_program("1,1,1,4,99,5,6,0,99"), [30,1,1,4,2,5,6,0,99]);
+    }
+
+    #[test]
+    fn trailing_newline() {
+        assert_eq!(super::parse_and_eval_program("1,0,0,0,99\n"), [2,0,0,0,99]);
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid program: value 4: -1 out of range")]
+    fn negative_value() {
+        super::parse_program("1,0,0,0,-1");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid program: value 1: invalid number `x`")]
+    fn invalid_number() {
+        super::parse_program("1,x,0,0,99");
+    }
+}
@@ -0,0 +1,107 @@
+use crate::sweep::{self, Intersection};
+use crate::wire::*;
+
+/// Any number of wires starting at the same port, with all their intersections.
+pub struct Network {
+    pub wires: Vec<Vec<WireSegment>>,
+    /// Sorted by point, see `sweep::intersections`.
+    pub intersections: Vec<Intersection>,
+}
+
+impl Network {
+    pub fn new(wires: Vec<Vec<WireSegment>>) -> Network {
+        let intersections = sweep::intersections(&wires);
+        Network {
+            wires,
+            intersections,
+        }
+    }
+
+    /// One wire description per line, empty lines are skipped. Errors come with their line,
+    /// counted from 1.
+    pub fn parse(text: &str) -> Result<Network, (usize, WireError)> {
+        let mut wires = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if !line.is_empty() {
+                wires.push(parse_wiredesc(line).map_err(|e| (i + 1, e))?);
+            }
+        }
+        Ok(Network::new(wires))
+    }
+
+    /// The intersection closest to the port where at least `k` different wires meet.
+    pub fn closest_meeting(&self, k: usize) -> Option<&Intersection> {
+        self.intersections
+            .iter()
+            .filter(|i| i.delays.len() >= k)
+            .min_by_key(|i| i.point.manhattan())
+    }
+
+    /// The point every wire passes through with the lowest sum of delays.
+    pub fn min_delay_all(&self) -> Option<&Intersection> {
+        self.intersections
+            .iter()
+            .filter(|i| i.delays.len() == self.wires.len())
+            .min_by_key(|i| i.signal_delay())
+    }
+
+    /// Points wires `i` and `j` share for every pair, self-intersections on the diagonal.
+    pub fn pairwise(&self) -> Vec<Vec<Vec<Point>>> {
+        let n = self.wires.len();
+        let mut matrix = vec![vec![Vec::new(); n]; n];
+        for intersection in self.intersections.iter() {
+            if intersection.is_self_intersection() {
+                let (wire, _) = intersection.delays[0];
+                matrix[wire][wire].push(intersection.point);
+                continue;
+            }
+            for (a, _) in intersection.delays.iter() {
+                for (b, _) in intersection.delays.iter().filter(|(b, _)| b != a) {
+                    matrix[*a][*b].push(intersection.point);
+                }
+            }
+        }
+        matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queries() {
+        let network = Network::parse("R4\nU1,R2,D2\n\nU2,R1,D3,R1,U1\n").unwrap();
+        assert_eq!(network.wires.len(), 3);
+
+        // (1,0) is as close, but comes later in point order.
+        assert_eq!(
+            network.closest_meeting(2).map(|i| i.point),
+            Some(Point { x: 0, y: 1 })
+        );
+        assert_eq!(
+            network.closest_meeting(3).map(|i| i.point),
+            Some(Point { x: 2, y: 0 })
+        );
+        assert_eq!(network.closest_meeting(4), None);
+        let all = network.min_delay_all().unwrap();
+        assert_eq!(all.delays, [(0, 2), (1, 4), (2, 8)]);
+        assert_eq!(all.signal_delay(), 14);
+
+        let matrix = network.pairwise();
+        assert_eq!(matrix[0][1], [Point { x: 2, y: 0 }]);
+        assert_eq!(matrix[1][0], matrix[0][1]);
+        assert_eq!(matrix[0][2], [Point { x: 1, y: 0 }, Point { x: 2, y: 0 }]);
+        assert_eq!(
+            matrix[1][2],
+            [
+                Point { x: 0, y: 1 },
+                Point { x: 1, y: 1 },
+                Point { x: 2, y: -1 },
+                Point { x: 2, y: 0 }
+            ]
+        );
+        assert!(matrix[0][0].is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use crate::network::Network;
+use crate::wire::Point;
+use std::fmt::Write as _;
+use std::io::Write;
+
+const BACKGROUND: [u8; 3] = [16, 16, 24];
+const PORT: [u8; 3] = [240, 240, 240];
+const INTERSECTION: [u8; 3] = [150, 150, 160];
+const CLOSEST: [u8; 3] = [250, 210, 40];
+const LOWEST_DELAY: [u8; 3] = [230, 60, 230];
+const WIRES: [[u8; 3]; 6] = [
+    [220, 60, 60],
+    [60, 140, 230],
+    [60, 200, 90],
+    [240, 140, 40],
+    [150, 90, 220],
+    [40, 200, 200],
+];
+
+/// Empty pixels around the layout.
+const MARGIN: f64 = 16.0;
+
+/// Maps wire coordinates, y up, onto image pixels, y down, so that the whole layout
+/// fits into `size` pixels on its longer side.
+struct Transform {
+    min: Point,
+    max: Point,
+    scale: f64,
+}
+
+impl Transform {
+    fn new(network: &Network, size: u32) -> Transform {
+        let mut min = Point { x: 0, y: 0 };
+        let mut max = min;
+        for segment in network.wires.iter().flatten() {
+            min.x = min.x.min(segment.min.x);
+            min.y = min.y.min(segment.min.y);
+            max.x = max.x.max(segment.max.x);
+            max.y = max.y.max(segment.max.y);
+        }
+        let extent = (max.x - min.x).max(max.y - min.y).max(1) as f64;
+        Transform {
+            min,
+            max,
+            scale: (size as f64 - 2.0 * MARGIN).max(1.0) / extent,
+        }
+    }
+
+    fn width(&self) -> u32 {
+        ((self.max.x - self.min.x) as f64 * self.scale + 2.0 * MARGIN).ceil() as u32 + 1
+    }
+
+    fn height(&self) -> u32 {
+        ((self.max.y - self.min.y) as f64 * self.scale + 2.0 * MARGIN).ceil() as u32 + 1
+    }
+
+    fn apply(&self, p: Point) -> (f64, f64) {
+        (
+            (p.x - self.min.x) as f64 * self.scale + MARGIN,
+            (self.max.y - p.y) as f64 * self.scale + MARGIN,
+        )
+    }
+}
+
+/// A dot on top of the wires.
+struct Marker {
+    point: Point,
+    color: [u8; 3],
+    radius: f64,
+}
+
+/// Intersections first, then the port and the highlighted intersections, so those end up on top.
+fn markers(network: &Network) -> Vec<Marker> {
+    let mut markers: Vec<Marker> = network
+        .intersections
+        .iter()
+        .filter(|i| !i.is_self_intersection())
+        .map(|i| Marker {
+            point: i.point,
+            color: INTERSECTION,
+            radius: 2.5,
+        })
+        .collect();
+    markers.push(Marker {
+        point: Point { x: 0, y: 0 },
+        color: PORT,
+        radius: 5.0,
+    });
+    if let Some(closest) = network.closest_meeting(2) {
+        markers.push(Marker {
+            point: closest.point,
+            color: CLOSEST,
+            radius: 5.0,
+        });
+    }
+    if let Some(lowest) = network.min_delay_all() {
+        markers.push(Marker {
+            point: lowest.point,
+            color: LOWEST_DELAY,
+            radius: 5.0,
+        });
+    }
+    markers
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Wires as polylines in their own colors, the port, every crossing between different wires,
+/// and the closest intersection and the one with the lowest delay highlighted.
+pub fn to_svg(network: &Network, size: u32) -> String {
+    let transform = Transform::new(network, size);
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+        w = transform.width(),
+        h = transform.height()
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+        hex(BACKGROUND)
+    )
+    .unwrap();
+    for (i, wire) in network.wires.iter().enumerate() {
+        let mut points = vec![transform.apply(Point { x: 0, y: 0 })];
+        points.extend(wire.iter().map(|segment| transform.apply(segment.end)));
+        let points: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect();
+        writeln!(
+            svg,
+            "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\"/>",
+            points.join(" "),
+            hex(WIRES[i % WIRES.len()])
+        )
+        .unwrap();
+    }
+    for marker in markers(network) {
+        let (x, y) = transform.apply(marker.point);
+        writeln!(
+            svg,
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"{}\" fill=\"{}\"/>",
+            x,
+            y,
+            marker.radius,
+            hex(marker.color)
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Width, height and RGB pixels of the same picture as `to_svg`.
+pub fn rasterize(network: &Network, size: u32) -> (u32, u32, Vec<u8>) {
+    let transform = Transform::new(network, size);
+    let (width, height) = (transform.width(), transform.height());
+    let mut pixels: Vec<u8> = BACKGROUND
+        .iter()
+        .copied()
+        .cycle()
+        .take((width * height * 3) as usize)
+        .collect();
+    let mut set = |x: f64, y: f64, color: [u8; 3]| {
+        let (x, y) = (x.round() as i64, y.round() as i64);
+        if x >= 0 && y >= 0 && x < width as i64 && y < height as i64 {
+            let i = ((x + y * width as i64) * 3) as usize;
+            pixels[i..(i + 3)].copy_from_slice(&color);
+        }
+    };
+    for (i, wire) in network.wires.iter().enumerate() {
+        for segment in wire.iter() {
+            let (x0, y0) = transform.apply(segment.start);
+            let (x1, y1) = transform.apply(segment.end);
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()).ceil().max(1.0);
+            for s in 0..=(steps as i64) {
+                let t = s as f64 / steps;
+                set(
+                    x0 + (x1 - x0) * t,
+                    y0 + (y1 - y0) * t,
+                    WIRES[i % WIRES.len()],
+                );
+            }
+        }
+    }
+    for marker in markers(network) {
+        let (cx, cy) = transform.apply(marker.point);
+        let r = marker.radius.ceil() as i64;
+        for dy in -r..=r {
+            for dx in -r..=r {
+                if ((dx * dx + dy * dy) as f64) <= marker.radius * marker.radius {
+                    set(cx + dx as f64, cy + dy as f64, marker.color);
+                }
+            }
+        }
+    }
+    (width, height, pixels)
+}
+
+pub fn write_png(network: &Network, out: impl Write, size: u32) -> Result<(), png::EncodingError> {
+    let (width, height, pixels) = rasterize(network, size);
+    let mut encoder = png::Encoder::new(out, width, height);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Network {
+        Network::parse("R8,U5,L5,D3\nU7,R6,D4,L4").unwrap()
+    }
+
+    #[test]
+    fn svg() {
+        let svg = to_svg(&sample(), 100);
+        // 8×7 units scaled to fit 100 - 2·16 pixels on the longer side.
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"101\" height=\"93\""));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert!(svg.contains("points=\"16.0,75.5 84.0,75.5 84.0,33.0"));
+        // Two intersections, the port and both highlights.
+        assert_eq!(svg.matches("<circle").count(), 5);
+        assert!(svg.contains(&format!("fill=\"{}\"", hex(CLOSEST))));
+        assert!(svg.contains(&format!("fill=\"{}\"", hex(LOWEST_DELAY))));
+    }
+
+    #[test]
+    fn png() {
+        let network = sample();
+        let mut png = Vec::new();
+        write_png(&network, &mut png, 100).unwrap();
+        let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (101, 93));
+        assert_eq!(pixels[..3], BACKGROUND);
+        let pixel = |x: usize, y: usize| {
+            let i = (x + y * 101) * 3;
+            [pixels[i], pixels[i + 1], pixels[i + 2]]
+        };
+        // The port at the left edge, 7 units below the top.
+        assert_eq!(pixel(16, 76), PORT);
+        // Closest intersection at 3,3.
+        assert_eq!(pixel(42, 50), CLOSEST);
+        // First wire going right from the port.
+        assert_eq!(pixel(60, 76), WIRES[0]);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::wire::*;
+
+/// A point where wires cross or overlap, or where a single wire crosses itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Intersection {
+    pub point: Point,
+    /// Index of every wire passing through `point` and the steps it takes to first get
+    /// there, sorted by wire.
+    pub delays: Vec<(usize, i32)>,
+}
+
+impl Intersection {
+    pub fn is_self_intersection(&self) -> bool {
+        self.delays.len() == 1
+    }
+
+    /// Sum of the delays of all wires meeting here.
+    pub fn signal_delay(&self) -> i32 {
+        self.delays.iter().map(|(_, delay)| delay).sum()
+    }
+}
+
+struct SweepSegment {
+    wire: usize,
+    index: usize,
+    segment: WireSegment,
+}
+
+/// All intersections between any of the given wires and of each wire with itself,
+/// sorted by point. The origin, where every wire starts, doesn't count. Delays are
+/// looked up in a `StepIndex` per wire.
+///
+/// Segments are swept by their left end, so only segments whose x ranges overlap
+/// get tested against each other.
+pub fn intersections(wires: &[Vec<WireSegment>]) -> Vec<Intersection> {
+    let mut segments: Vec<SweepSegment> = wires
+        .iter()
+        .enumerate()
+        .flat_map(|(wire, segments)| {
+            // Zero-length steps are dropped before numbering, so the segments on either
+            // side of one still count as consecutive.
+            segments
+                .iter()
+                .filter(|s| s.len > 0)
+                .enumerate()
+                .map(move |(index, segment)| SweepSegment {
+                    wire,
+                    index,
+                    segment: *segment,
+                })
+        })
+        .collect();
+    segments.sort_by_key(|s| s.segment.min.x);
+
+    // (point, wire, segment index) for every segment passing through an intersection.
+    let mut hits: Vec<(Point, usize, usize)> = Vec::new();
+    let mut active: Vec<&SweepSegment> = Vec::new();
+    for s in segments.iter() {
+        active.retain(|a| a.segment.max.x >= s.segment.min.x);
+        for a in active.iter() {
+            if a.segment.max.y < s.segment.min.y || a.segment.min.y > s.segment.max.y {
+                continue;
+            }
+            for p in a.segment.shared_points(&s.segment) {
+                if p == (Point { x: 0, y: 0 }) {
+                    continue;
+                }
+                // Consecutive segments of a wire always share the corner between them.
+                let is_joint = a.wire == s.wire
+                    && ((a.index + 1 == s.index && p == a.segment.end)
+                        || (s.index + 1 == a.index && p == s.segment.end));
+                if is_joint {
+                    continue;
+                }
+                hits.push((p, a.wire, a.index));
+                hits.push((p, s.wire, s.index));
+            }
+        }
+        active.push(s);
+    }
+    hits.sort_unstable();
+    hits.dedup();
+
+    let steps: Vec<StepIndex> = wires.iter().map(|wire| StepIndex::new(wire)).collect();
+    let mut intersections: Vec<Intersection> = Vec::new();
+    for (point, wire, _) in hits {
+        let delay = steps[wire].first_visit(point).unwrap();
+        match intersections.last_mut() {
+            Some(last) if last.point == point => {
+                if last.delays.last().map(|(w, _)| *w) != Some(wire) {
+                    last.delays.push((wire, delay));
+                }
+            }
+            _ => intersections.push(Intersection {
+                point,
+                delays: vec![(wire, delay)],
+            }),
+        }
+    }
+    intersections
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wires(descs: &[&str]) -> Vec<Vec<WireSegment>> {
+        descs
+            .iter()
+            .map(|desc| parse_wiredesc(desc).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn crossing() {
+        let found = intersections(&wires(&["R8,U5,L5,D3", "U7,R6,D4,L4"]));
+        assert_eq!(
+            found,
+            [
+                Intersection {
+                    point: Point { x: 3, y: 3 },
+                    delays: vec![(0, 20), (1, 20)],
+                },
+                Intersection {
+                    point: Point { x: 6, y: 5 },
+                    delays: vec![(0, 15), (1, 15)],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn collinear_overlap() {
+        let found = intersections(&wires(&["R10", "U2,R3,D2,R4"]));
+        let points: Vec<Point> = found.iter().map(|i| i.point).collect();
+        assert_eq!(
+            points,
+            (3..=7).map(|x| Point { x, y: 0 }).collect::<Vec<_>>()
+        );
+        assert_eq!(found[0].delays, [(0, 3), (1, 7)]);
+        assert_eq!(found[4].delays, [(0, 7), (1, 11)]);
+
+        // Vertical overlap in opposite directions.
+        let found = intersections(&wires(&["U5", "R1,U4,L1,D2"]));
+        assert_eq!(found.len(), 3);
+        assert_eq!(found[0].point, Point { x: 0, y: 2 });
+        assert_eq!(found[0].delays, [(0, 2), (1, 8)]);
+    }
+
+    #[test]
+    fn self_intersections() {
+        let found = intersections(&wires(&["R4,U2,L2,D4"]));
+        assert_eq!(
+            found,
+            [Intersection {
+                point: Point { x: 2, y: 0 },
+                delays: vec![(0, 2)],
+            }]
+        );
+        assert!(found[0].is_self_intersection());
+
+        // Turning back onto itself overlaps everything but the corner.
+        let found = intersections(&wires(&["R5,L3"]));
+        let points: Vec<Point> = found.iter().map(|i| i.point).collect();
+        assert_eq!(
+            points,
+            (2..=4).map(|x| Point { x, y: 0 }).collect::<Vec<_>>()
+        );
+
+        // A zero-length step in between doesn't turn the corner into a crossing.
+        let found = intersections(&wires(&["R2,U0,L1"]));
+        assert_eq!(
+            found,
+            [Intersection {
+                point: Point { x: 1, y: 0 },
+                delays: vec![(0, 1)],
+            }]
+        );
+    }
+
+    #[test]
+    fn three_wires() {
+        let found = intersections(&wires(&["R4", "U1,R2,D1", "D1,R2,U1"]));
+        assert_eq!(
+            found,
+            [Intersection {
+                point: Point { x: 2, y: 0 },
+                delays: vec![(0, 2), (1, 4), (2, 4)],
+            }]
+        );
+        assert_eq!(found[0].signal_delay(), 10);
+    }
+
+    #[test]
+    fn diagonals() {
+        let found = intersections(&wires(&["NE4,L3", "R4,NW2,U3"]));
+        let points: Vec<Point> = found.iter().map(|i| i.point).collect();
+        assert_eq!(points, [Point { x: 2, y: 2 }, Point { x: 2, y: 4 }]);
+        assert_eq!(found[0].delays, [(0, 2), (1, 6)]);
+        assert_eq!(found[1].delays, [(0, 6), (1, 8)]);
+    }
+
+    #[test]
+    fn delays_after_self_crossing() {
+        // The first wire loops through 2,0 at steps 2 and 10 and ends at 2,-1 after 11 steps.
+        let found = intersections(&wires(&["R4,U2,L2,D3", "NE1,SE1,D1"]));
+        let points: Vec<Point> = found.iter().map(|i| i.point).collect();
+        assert_eq!(points, [Point { x: 2, y: -1 }, Point { x: 2, y: 0 }]);
+        assert_eq!(found[0].delays, [(0, 11), (1, 3)]);
+        assert_eq!(found[1].delays, [(0, 2), (1, 2)]);
+
+        // 0,1 is reached after 5 steps and again after 11, on a segment crossing the other wire.
+        let found = intersections(&wires(&["R2,U1,L3,D2,R1,U2", "U3"]));
+        assert_eq!(
+            found,
+            [Intersection {
+                point: Point { x: 0, y: 1 },
+                delays: vec![(0, 5), (1, 1)],
+            }]
+        );
+    }
+}
@@ -0,0 +1,341 @@
+use std::cmp;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn manhattan(&self) -> i32 {
+        self.x.abs() + self.y.abs()
+    }
+}
+
+fn cross(a: Point, b: Point) -> i64 {
+    a.x as i64 * b.y as i64 - a.y as i64 * b.x as i64
+}
+
+fn dot(a: Point, b: Point) -> i64 {
+    a.x as i64 * b.x as i64 + a.y as i64 * b.y as i64
+}
+
+/// The eight step directions, clockwise from up in 45° increments.
+const HEADINGS: [(&str, Point); 8] = [
+    ("U", Point { x: 0, y: 1 }),
+    ("NE", Point { x: 1, y: 1 }),
+    ("R", Point { x: 1, y: 0 }),
+    ("SE", Point { x: 1, y: -1 }),
+    ("D", Point { x: 0, y: -1 }),
+    ("SW", Point { x: -1, y: -1 }),
+    ("L", Point { x: -1, y: 0 }),
+    ("NW", Point { x: -1, y: 1 }),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum WireError {
+    UnknownDirection {
+        step: usize,
+        token: String,
+    },
+    InvalidLength {
+        step: usize,
+        token: String,
+    },
+    /// Turns have to be multiples of 45°.
+    InvalidTurn {
+        step: usize,
+        degrees: i32,
+    },
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WireError::UnknownDirection { step, token } => {
+                write!(f, "step {}: unknown direction in `{}`", step, token)
+            }
+            WireError::InvalidLength { step, token } => {
+                write!(f, "step {}: invalid length in `{}`", step, token)
+            }
+            WireError::InvalidTurn { step, degrees } => write!(
+                f,
+                "step {}: can only turn by multiples of 45°, not {}",
+                step, degrees
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+pub struct WireSegment {
+    pub start: Point,
+    pub end: Point,
+    /// One of `HEADINGS`, the offset of a single step.
+    pub direction: Point,
+    pub min: Point,
+    pub max: Point,
+    pub len: i32,
+    /// Steps the wire takes before reaching `start`.
+    pub delay: i32,
+}
+
+impl WireSegment {
+    fn point_at(&self, t: i64) -> Point {
+        Point {
+            x: self.start.x + (t * self.direction.x as i64) as i32,
+            y: self.start.y + (t * self.direction.y as i64) as i32,
+        }
+    }
+
+    /// Every lattice point both segments pass through. Crossings between lattice points,
+    /// as two diagonals can have, aren't reached after a whole number of steps and don't count.
+    pub fn shared_points(&self, other: &WireSegment) -> Vec<Point> {
+        let offset = Point {
+            x: other.start.x - self.start.x,
+            y: other.start.y - self.start.y,
+        };
+        let denominator = cross(self.direction, other.direction);
+        if denominator != 0 {
+            // Solve start + t·direction = other.start + u·other.direction.
+            let t = cross(offset, other.direction);
+            let u = cross(offset, self.direction);
+            if t % denominator != 0 || u % denominator != 0 {
+                return Vec::new();
+            }
+            let (t, u) = (t / denominator, u / denominator);
+            if (0..=self.len as i64).contains(&t) && (0..=other.len as i64).contains(&u) {
+                return vec![self.point_at(t)];
+            }
+            return Vec::new();
+        }
+        if cross(offset, self.direction) != 0 {
+            return Vec::new();
+        }
+
+        // Collinear: `other` covers the steps from t0 to t1 of this segment.
+        let length = dot(self.direction, self.direction);
+        let t0 = dot(offset, self.direction) / length;
+        let t1 = t0 + other.len as i64 * dot(other.direction, self.direction) / length;
+        let from = cmp::max(0, cmp::min(t0, t1));
+        let to = cmp::min(self.len as i64, cmp::max(t0, t1));
+        (from..=to).map(|t| self.point_at(t)).collect()
+    }
+}
+
+/// Steps a wire takes to first reach each lattice point on it. Later visits, after the wire
+/// looped back, don't count as the signal already got there.
+pub struct StepIndex {
+    steps: HashMap<Point, i32>,
+}
+
+impl StepIndex {
+    pub fn new(wire: &[WireSegment]) -> StepIndex {
+        let mut steps = HashMap::new();
+        steps.insert(Point { x: 0, y: 0 }, 0);
+        for segment in wire.iter() {
+            for t in 1..=segment.len {
+                steps
+                    .entry(segment.point_at(t as i64))
+                    .or_insert(segment.delay + t);
+            }
+        }
+        StepIndex { steps }
+    }
+
+    pub fn first_visit(&self, p: Point) -> Option<i32> {
+        self.steps.get(&p).copied()
+    }
+}
+
+fn parse_length(step: usize, token: &str, digits: &str) -> Result<i32, WireError> {
+    match digits.parse::<i32>() {
+        Ok(length) if length >= 0 => Ok(length),
+        _ => Err(WireError::InvalidLength {
+            step,
+            token: token.to_string(),
+        }),
+    }
+}
+
+/// Comma separated steps, each either a direction with a length (`R8`, `U5`, `NE3`, `SW2`, ...),
+/// `F<n>` to go on in the current direction, or `TL<degrees>`/`TR<degrees>` to turn left or right.
+/// Wires start out heading up. Steps are counted from 1 in errors.
+pub fn parse_wiredesc(wiredesc: &str) -> Result<Vec<WireSegment>, WireError> {
+    let mut pos = Point { x: 0, y: 0 };
+    let mut heading = 0;
+    let mut delay = 0;
+    let mut segments = Vec::new();
+    for (i, token) in wiredesc.split(',').enumerate() {
+        let step = i + 1;
+        let step_len = if token.starts_with("TL") || token.starts_with("TR") {
+            let degrees = parse_length(step, token, &token[2..])?;
+            if degrees % 45 != 0 {
+                return Err(WireError::InvalidTurn { step, degrees });
+            }
+            let turn = (degrees / 45) as usize % HEADINGS.len();
+            heading = if token.starts_with("TR") {
+                (heading + turn) % HEADINGS.len()
+            } else {
+                (heading + HEADINGS.len() - turn) % HEADINGS.len()
+            };
+            continue;
+        } else if let Some(digits) = token.strip_prefix('F') {
+            parse_length(step, token, digits)?
+        } else {
+            // Longest match first, so that `SE` isn't taken for an unknown `S`.
+            let (name, _) = HEADINGS
+                .iter()
+                .filter(|(name, _)| token.starts_with(name))
+                .max_by_key(|(name, _)| name.len())
+                .ok_or_else(|| WireError::UnknownDirection {
+                    step,
+                    token: token.to_string(),
+                })?;
+            heading = HEADINGS.iter().position(|(n, _)| n == name).unwrap();
+            parse_length(step, token, &token[name.len()..])?
+        };
+        let direction = HEADINGS[heading].1;
+        let old_pos = pos;
+        pos.x += direction.x * step_len;
+        pos.y += direction.y * step_len;
+
+        segments.push(WireSegment {
+            start: old_pos,
+            end: pos,
+            direction,
+            min: Point {
+                x: cmp::min(old_pos.x, pos.x),
+                y: cmp::min(old_pos.y, pos.y),
+            },
+            max: Point {
+                x: cmp::max(old_pos.x, pos.x),
+                y: cmp::max(old_pos.y, pos.y),
+            },
+            len: step_len,
+            delay,
+        });
+        delay += step_len;
+    }
+
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ends(wiredesc: &str) -> Vec<Point> {
+        parse_wiredesc(wiredesc)
+            .unwrap()
+            .iter()
+            .map(|s| s.end)
+            .collect()
+    }
+
+    fn segment(wiredesc: &str) -> WireSegment {
+        *parse_wiredesc(wiredesc).unwrap().last().unwrap()
+    }
+
+    #[test]
+    fn grammar() {
+        assert_eq!(
+            ends("R2,NE2,SW1,D1"),
+            [
+                Point { x: 2, y: 0 },
+                Point { x: 4, y: 2 },
+                Point { x: 3, y: 1 },
+                Point { x: 3, y: 0 }
+            ]
+        );
+        // Relative steps start heading up and follow the last direction.
+        assert_eq!(
+            ends("F2,TR90,F1,TL45,F1,L1,TL180,F3"),
+            [
+                Point { x: 0, y: 2 },
+                Point { x: 1, y: 2 },
+                Point { x: 2, y: 3 },
+                Point { x: 1, y: 3 },
+                Point { x: 4, y: 3 }
+            ]
+        );
+        assert_eq!(parse_wiredesc("NE3,R1").unwrap()[1].delay, 3);
+        let steps = StepIndex::new(&parse_wiredesc("U1,SE4").unwrap());
+        assert_eq!(steps.first_visit(Point { x: 2, y: -1 }), Some(3));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            parse_wiredesc("R8,X2").err(),
+            Some(WireError::UnknownDirection {
+                step: 2,
+                token: "X2".to_string()
+            })
+        );
+        assert_eq!(
+            parse_wiredesc("R8,U5,NE").err(),
+            Some(WireError::InvalidLength {
+                step: 3,
+                token: "NE".to_string()
+            })
+        );
+        assert_eq!(
+            parse_wiredesc("F-1").err(),
+            Some(WireError::InvalidLength {
+                step: 1,
+                token: "F-1".to_string()
+            })
+        );
+        assert_eq!(
+            parse_wiredesc("R1,TL30").err(),
+            Some(WireError::InvalidTurn {
+                step: 2,
+                degrees: 30
+            })
+        );
+    }
+
+    #[test]
+    fn shared_points() {
+        // Diagonal through a vertical line.
+        assert_eq!(
+            segment("NE4").shared_points(&segment("R2,U3")),
+            [Point { x: 2, y: 2 }]
+        );
+        // Two diagonals crossing between lattice points.
+        assert!(segment("NE1").shared_points(&segment("R1,NW1")).is_empty());
+        // Two diagonals crossing on one.
+        assert_eq!(
+            segment("NE2").shared_points(&segment("R2,NW2")),
+            [Point { x: 1, y: 1 }]
+        );
+        // Collinear diagonals in opposite directions.
+        assert_eq!(
+            segment("NE4").shared_points(&segment("NE5,SW3")),
+            [
+                Point { x: 2, y: 2 },
+                Point { x: 3, y: 3 },
+                Point { x: 4, y: 4 }
+            ]
+        );
+        // Parallel, but offset.
+        assert!(segment("NE4").shared_points(&segment("R1,NE4")).is_empty());
+        // Out of reach.
+        assert!(segment("R2").shared_points(&segment("R3,U2")).is_empty());
+    }
+
+    #[test]
+    fn first_visits() {
+        let steps = StepIndex::new(&parse_wiredesc("R4,U2,L2,D4,L2,U2").unwrap());
+        // The wire gets back to the port after 16 steps.
+        assert_eq!(steps.first_visit(Point { x: 0, y: 0 }), Some(0));
+        assert_eq!(steps.first_visit(Point { x: 2, y: 0 }), Some(2));
+        assert_eq!(steps.first_visit(Point { x: 2, y: -1 }), Some(11));
+        assert_eq!(steps.first_visit(Point { x: 0, y: -2 }), Some(14));
+        assert_eq!(steps.first_visit(Point { x: 1, y: 0 }), Some(1));
+        assert_eq!(steps.first_visit(Point { x: 5, y: 0 }), None);
+    }
+}
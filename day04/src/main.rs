@@ -1,3 +1,5 @@
+#![allow(clippy::bool_assert_comparison, clippy::items_after_test_module)]
+
 fn is_valid_pass_part1(num: i32) -> bool {
     let num_str = num.to_string();
     let num_bytes = num_str.as_bytes();
@@ -1,11 +1,20 @@
+mod parser;
+
+use std::convert::TryFrom;
 fn parse_program(puzzle_input: &str) -> Vec<i32> {
-    puzzle_input
-        .split(',')
-        .map(|line| line.parse::<i32>().unwrap())
+    let values = parser::parse_intcode(puzzle_input)
+        .unwrap_or_else(|err| panic!("invalid program: {}", err));
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            i32::try_from(*v)
+                .unwrap_or_else(|_| panic!("invalid program: value {}: {} out of range", i, v))
+        })
         .collect()
 }
 
-fn eval_program(program: &mut Vec<i32>, input: i32) -> Vec<i32> {
+fn eval_program(program: &mut [i32], input: i32) -> Vec<i32> {
     let mut pc = 0; // program counter
     let mut output = Vec::new();
     loop {
@@ -84,6 +93,14 @@ fn parse_and_eval_program(puzzle_input: &str, input: i32) -> Vec<i32> {
     eval_program(&mut program, input)
 }
 
+fn main() {
+    let puzzle_input = include_str!("input.txt");
+    //parse_and_eval_program("1002,4,3,4,33", 0);
+
+    println!("part 1 {:?}", parse_and_eval_program(puzzle_input, 1));
+    println!("part 2 {:?}", parse_and_eval_program(puzzle_input, 5));
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -116,12 +133,15 @@ mod tests {
         assert_eq!(super::parse_and_eval_program("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99", 8), [1000]);
         assert_eq!(super::parse_and_eval_program("3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99", 123), [1001]);
     }
-}
 
-fn main() {
-    let puzzle_input = include_str!("input.txt");
-    //parse_and_eval_program("1002,4,3,4,33", 0);
+    #[test]
+    fn trailing_newline() {
+        assert_eq!(super::parse_and_eval_program("3,0,4,0,99\n", 42), [42]);
+    }
 
-    println!("part 1 {:?}", parse_and_eval_program(puzzle_input, 1));
-    println!("part 2 {:?}", parse_and_eval_program(puzzle_input, 5));
+    #[test]
+    #[should_panic(expected = "invalid program: value 5: 4294967296 out of range")]
+    fn value_out_of_range() {
+        super::parse_program("3,0,4,0,99,4294967296");
+    }
 }
@@ -16,7 +16,7 @@ fn parse_connections(puzzle_input: &str) -> HashMap<&str, Vec<&str>> {
 }
 
 fn find_root<'a>(connections: &'a HashMap<&str, Vec<&str>>) -> &'a str {
-    let mut candidates: HashSet<&'a str> = connections.keys().map(|k| *k).collect();
+    let mut candidates: HashSet<&'a str> = connections.keys().copied().collect();
     for edges in connections.values() {
         for target in edges {
             candidates.remove(target);
@@ -52,10 +52,7 @@ fn common_ancestor<'a>(connections: &'a HashMap<&str, Vec<&str>>, root: &'a str,
         return Some(b);
     }
 
-    let edges = match connections.get(root) {
-        Some(val) => val,
-        None => return None,
-    };
+    let edges = connections.get(root)?;
     let nodes: Vec<&str> = edges.iter().filter_map(|edge| common_ancestor(connections, edge, a, b)).collect();
     if nodes.contains(&a) && nodes.contains(&b) {
         Some(root)
@@ -74,6 +71,15 @@ fn graph_distance(connections: &HashMap<&str, Vec<&str>>, depths: &HashMap<&str,
     depths[from] + depths[to] - depths[ancestor.unwrap()] * 2 - 2
 }
 
+fn main() {
+    let puzzle_input = include_str!("input.txt");
+    let connections = parse_connections(puzzle_input);
+    let root = find_root(&connections);
+    let depths = compute_level_depths(&connections, root);
+    println!("part 1 {}", depths.values().sum::<u32>());
+    println!("part 2 {}", graph_distance(&connections, &depths, root));
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -119,12 +125,3 @@ I)SAN",
         assert_eq!(super::graph_distance(&connections, &depths, root), 4);
     }
 }
-
-fn main() {
-    let puzzle_input = include_str!("input.txt");
-    let connections = parse_connections(puzzle_input);
-    let root = find_root(&connections);
-    let depths = compute_level_depths(&connections, root);
-    println!("part 1 {}", depths.values().sum::<u32>());
-    println!("part 2 {}", graph_distance(&connections, &depths, root));
-}
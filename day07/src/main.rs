@@ -1,4 +1,7 @@
+mod parser;
+
 use std::collections::VecDeque;
+use std::convert::TryFrom;
 
 enum ProgramResult {
     WaitForInputAt,
@@ -25,9 +28,15 @@ impl ProgramState {
 }
 
 fn parse_program(puzzle_input: &str) -> Vec<i32> {
-    puzzle_input
-        .split(',')
-        .map(|line| line.parse::<i32>().unwrap())
+    let values = parser::parse_intcode(puzzle_input)
+        .unwrap_or_else(|err| panic!("invalid program: {}", err));
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, v)| {
+            i32::try_from(*v)
+                .unwrap_or_else(|_| panic!("invalid program: value {}: {} out of range", i, v))
+        })
         .collect()
 }
 
@@ -101,11 +110,11 @@ fn eval_program(state: &mut ProgramState) -> ProgramResult {
     }
 }
 
-fn run_amplifiers<'a>(program: &Vec<i32>, configs: impl Iterator<Item = &'a i32>) -> i32 {
+fn run_amplifiers<'a>(program: &[i32], configs: impl Iterator<Item = &'a i32>) -> i32 {
     let mut signal = 0;
     for config in configs {
         let mut state = ProgramState {
-            program: program.clone(),
+            program: program.to_vec(),
             pc: 0,
             inputs: vec![*config, signal].into_iter().collect(),
         };
@@ -117,24 +126,24 @@ fn run_amplifiers<'a>(program: &Vec<i32>, configs: impl Iterator<Item = &'a i32>
     signal
 }
 
-fn run_amplifier_loop<'a>(program: &Vec<i32>, configs: impl Iterator<Item = &'a i32>) -> i32 {
+fn run_amplifier_loop<'a>(program: &[i32], configs: impl Iterator<Item = &'a i32>) -> i32 {
     let mut signal = Some(0);
     let mut final_signal = -1;
 
     let mut amplifiers: Vec<ProgramState> = configs
         .map(|c| ProgramState {
-            program: program.clone(),
+            program: program.to_vec(),
             pc: 0,
             inputs: vec![*c].into_iter().collect(),
         })
         .collect();
 
     loop {
-        for mut amplifier in amplifiers.iter_mut() {
+        for amplifier in amplifiers.iter_mut() {
             if let Some(s) = signal {
                 amplifier.inputs.push_back(s);
             }
-            signal = match eval_program(&mut amplifier) {
+            signal = match eval_program(amplifier) {
                 ProgramResult::WaitForInputAt => None,
                 ProgramResult::Output(out) => Some(out),
                 ProgramResult::Halted => return final_signal,
@@ -144,22 +153,32 @@ fn run_amplifier_loop<'a>(program: &Vec<i32>, configs: impl Iterator<Item = &'a
     }
 }
 
-fn find_max_amplifier_config(program: &Vec<i32>) -> i32 {
+fn find_max_amplifier_config(program: &[i32]) -> i32 {
     let base_config = [0, 1, 2, 3, 4];
     permute::permutations_of(&base_config)
-        .map(|permutation| run_amplifiers(&program, permutation))
+        .map(|permutation| run_amplifiers(program, permutation))
         .max()
         .unwrap()
 }
 
-fn find_max_amplifier_loop(program: &Vec<i32>) -> i32 {
+fn find_max_amplifier_loop(program: &[i32]) -> i32 {
     let base_config = [9, 8, 7, 6, 5];
     permute::permutations_of(&base_config)
-        .map(|permutation| run_amplifier_loop(&program, permutation))
+        .map(|permutation| run_amplifier_loop(program, permutation))
         .max()
         .unwrap()
 }
 
+fn main() {
+    let program = parse_program("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
+    assert_eq!(find_max_amplifier_config(&program), 43210);
+
+    let puzzle_input = include_str!("input.txt");
+    let program = parse_program(puzzle_input);
+    println!("part 1 {:?}", find_max_amplifier_config(&program));
+    println!("part 2 {:?}", find_max_amplifier_loop(&program));
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -187,14 +206,10 @@ mod tests {
             assert_eq!(super::find_max_amplifier_loop(&program), 139629729);
         }
     }
-}
-
-fn main() {
-    let program = parse_program("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0");
-    assert_eq!(find_max_amplifier_config(&program), 43210);
 
-    let puzzle_input = include_str!("input.txt");
-    let program = parse_program(puzzle_input);
-    println!("part 1 {:?}", find_max_amplifier_config(&program));
-    println!("part 2 {:?}", find_max_amplifier_loop(&program));
+    #[test]
+    fn trailing_newline() {
+        let program = super::parse_program("3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0\n");
+        assert_eq!(super::find_max_amplifier_config(&program), 43210);
+    }
 }
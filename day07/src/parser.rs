@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// Index and text of a value that isn't a number.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub position: usize,
+    pub token: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "value {}: invalid number `{}`",
+            self.position, self.token
+        )
+    }
+}
+
+/// Parses comma separated Intcode, ignoring whitespace around values such as a trailing newline.
+pub fn parse_intcode(text: &str) -> Result<Vec<i64>, ParseError> {
+    text.split(',')
+        .enumerate()
+        .map(|(position, token)| {
+            let token = token.trim();
+            token.parse().map_err(|_| ParseError {
+                position,
+                token: token.to_string(),
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,122 @@
+use crate::sif::*;
+
+/// How often each digit occurs, one histogram per layer.
+pub fn histograms(image: &SpaceImage) -> Vec<[usize; 10]> {
+    image
+        .layers()
+        .map(|layer| {
+            let mut counts = [0; 10];
+            for digit in layer {
+                counts[*digit as usize] += 1;
+            }
+            counts
+        })
+        .collect()
+}
+
+/// Index of the topmost opaque layer for every pixel, `None` for pixels that are
+/// transparent in all layers.
+pub fn deciding_layers(image: &SpaceImage) -> Vec<Option<usize>> {
+    let mut deciding = vec![None; image.layer_size()];
+    for (l, layer) in image.layers().enumerate() {
+        for (pixel, digit) in deciding.iter_mut().zip(layer.iter()) {
+            if pixel.is_none() && *digit != TRANSPARENT {
+                *pixel = Some(l);
+            }
+        }
+    }
+    deciding
+}
+
+/// Black and white as in the final render in `main`, which shows everything else as `?`. Here
+/// transparent pixels are shown as `.` and other colors as their digit.
+fn pixel_char(digit: u8) -> char {
+    match digit {
+        0 => '█',
+        1 => ' ',
+        TRANSPARENT => '.',
+        _ => std::char::from_digit(digit as u32, 10).unwrap(),
+    }
+}
+
+fn render(width: usize, pixels: impl Iterator<Item = char>) -> String {
+    let mut text = String::new();
+    for (i, c) in pixels.enumerate() {
+        text.push(c);
+        if (i + 1) % width == 0 {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+pub fn render_layer(image: &SpaceImage, layer: usize) -> Option<String> {
+    let layer = image.layers().nth(layer)?;
+    Some(render(image.width, layer.iter().map(|d| pixel_char(*d))))
+}
+
+/// Composition of the layers `0..=layer`, i.e. what the image looks like when only these are stacked.
+pub fn render_stack(image: &SpaceImage, layer: usize) -> Option<String> {
+    if layer >= image.layers().count() {
+        return None;
+    }
+    let stack = SpaceImage {
+        width: image.width,
+        height: image.height,
+        data: image.data[..(layer + 1) * image.layer_size()].to_vec(),
+    };
+    Some(render(
+        image.width,
+        stack.compose().into_iter().map(pixel_char),
+    ))
+}
+
+/// Deciding layer of every pixel in base 36 (wrapping for deeper layers), `?` if undecided.
+pub fn render_deciding_layers(image: &SpaceImage) -> String {
+    render(
+        image.width,
+        deciding_layers(image).into_iter().map(|l| match l {
+            Some(l) => std::char::from_digit((l % 36) as u32, 36).unwrap(),
+            None => '?',
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> SpaceImage {
+        SpaceImage::parse("022211222212000092222222", 2, 2).unwrap()
+    }
+
+    #[test]
+    fn statistics() {
+        let image = sample();
+        let histograms = histograms(&image);
+        assert_eq!(histograms.len(), 6);
+        assert_eq!(histograms[0], [1, 0, 3, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(histograms[3], [4, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(histograms[4][9], 1);
+        assert_eq!(
+            deciding_layers(&image),
+            [Some(0), Some(1), Some(2), Some(3)]
+        );
+
+        let image = SpaceImage::parse("22120201", 2, 2).unwrap();
+        assert_eq!(deciding_layers(&image), [Some(1), None, Some(0), Some(1)]);
+        assert_eq!(render_deciding_layers(&image), "1?\n01\n");
+    }
+
+    #[test]
+    fn rendering() {
+        let image = sample();
+        assert_eq!(render_layer(&image, 0).unwrap(), "█.\n..\n");
+        assert_eq!(render_layer(&image, 4).unwrap(), "9.\n..\n");
+        assert_eq!(render_layer(&image, 6), None);
+        assert_eq!(render_stack(&image, 0).unwrap(), "█.\n..\n");
+        assert_eq!(render_stack(&image, 1).unwrap(), "█ \n..\n");
+        assert_eq!(render_stack(&image, 5).unwrap(), "█ \n █\n");
+        assert_eq!(render_stack(&image, 6), None);
+    }
+}
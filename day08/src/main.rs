@@ -1,58 +1,145 @@
+mod inspect;
+mod ocr;
+mod palette;
+mod sif;
+mod stream;
+
+use sif::SpaceImage;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
 static WIDTH: usize = 25;
 static HEIGHT: usize = 6;
-static LAYER_STRIDE: usize = WIDTH * HEIGHT;
-
-fn part1(image_layers: &Vec<&[u8]>) -> usize {
-    let mut fewest_zerocount_layer = image_layers[0];
-    let mut fewest_zerocount_layer_zerocount = LAYER_STRIDE;
-    for layer in image_layers {
-        let zerocount = layer.iter().filter(|p| **p == 0).count();
-        if zerocount < fewest_zerocount_layer_zerocount {
-            fewest_zerocount_layer = layer;
-            fewest_zerocount_layer_zerocount = zerocount;
+
+fn read_image(width: usize, height: usize, final_image: &[u8]) -> Result<String, ocr::OcrError> {
+    ocr::recognize(&ocr::Bitmap::from_fn(width, height, |x, y| {
+        final_image[x + y * width] == 1
+    }))
+}
+
+fn print_image(width: usize, height: usize, checksum: Option<usize>, final_image: &[u8]) {
+    // PART 1
+    match checksum {
+        Some(checksum) => println!("num layers {}", checksum),
+        None => {
+            println!("no layers");
+            return;
         }
     }
-    fewest_zerocount_layer.iter().filter(|p| **p == 1).count()
-        * fewest_zerocount_layer.iter().filter(|p| **p == 2).count()
-}
 
-fn main() {
-    let image_raw: Vec<u8> = include_str!("input.txt")
-        .as_bytes()
-        .iter()
-        .map(|c| (*c as char).to_digit(10).unwrap() as u8)
-        .collect();
-
-    let mut image_layers = Vec::new();
-    let num_layers = image_raw.len() / WIDTH / HEIGHT;
-    for l in 0..num_layers {
-        image_layers.push(&image_raw[(l * LAYER_STRIDE)..((l + 1) * LAYER_STRIDE)]);
+    // PART 2
+    for y in 0..height {
+        for x in 0..width {
+            print!(
+                "{}",
+                match final_image[x + y * width] {
+                    0 => '█',
+                    1 => ' ',
+                    _ => '?',
+                }
+            );
+        }
+        println!();
     }
+    match read_image(width, height, final_image) {
+        Ok(text) => println!("{}", text),
+        Err(error) => println!("{}", error),
+    }
+}
 
-    // PART 1
-    println!("num layers {}", part1(&image_layers));
+fn main() {
+    let image = SpaceImage::parse(include_str!("input.txt"), WIDTH, HEIGHT)
+        .unwrap_or_else(|e| panic!("{}", e));
 
-    // PART 2
-    let mut final_image = vec![2u8; LAYER_STRIDE];
-    for layer in image_layers.iter().rev() {
-        for i in 0..LAYER_STRIDE {
-            final_image[i] = match layer[i] {
-                2 => final_image[i],
-                1 => 1,
-                0 => 0,
-                _ => panic!("unknown color val"),
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    match args[..] {
+        [] => print_image(WIDTH, HEIGHT, image.checksum(), &image.compose()),
+        ["--stream", path, width, height] => {
+            let (width, height) = (width.parse().unwrap(), height.parse().unwrap());
+            let summary = if path == "-" {
+                stream::decode(std::io::stdin().lock(), width, height)
+            } else {
+                stream::decode(File::open(path).unwrap(), width, height)
             }
+            .unwrap_or_else(|e| panic!("{}", e));
+            println!("{} layers", summary.layers);
+            print_image(
+                summary.width,
+                summary.height,
+                summary.checksum,
+                &summary.composed,
+            );
         }
-    }
-    for y in 0..HEIGHT {
-        for x in 0..WIDTH {
-            print!("{}", match final_image[x + y * WIDTH] {
-                0 => '█',
-                1 => ' ',
-                2 => '?',
-                _ => panic!("unknown color val"),
-            });
+        ["--histogram"] => {
+            println!("layer {}", (0..10).map(|d| format!("{:>4}", d)).collect::<String>());
+            for (l, counts) in inspect::histograms(&image).iter().enumerate() {
+                let counts: String = counts.iter().map(|c| format!("{:>4}", c)).collect();
+                println!("{:>5} {}", l, counts);
+            }
+        }
+        ["--layer", layer] => match inspect::render_layer(&image, layer.parse().unwrap()) {
+            Some(text) => print!("{}", text),
+            None => println!("image has {} layers", image.layers().count()),
+        },
+        ["--stack", layer] => match inspect::render_stack(&image, layer.parse().unwrap()) {
+            Some(text) => print!("{}", text),
+            None => println!("image has {} layers", image.layers().count()),
+        },
+        ["--deciding"] => print!("{}", inspect::render_deciding_layers(&image)),
+        ["--optimize"] => {
+            let optimized = image.optimize();
+            eprintln!(
+                "{} of {} layers decide pixels",
+                optimized.layers().count(),
+                image.layers().count()
+            );
+            println!("{}", optimized.encode());
+        }
+        ["--adversarial", layers, seed] => {
+            let target = image.compose();
+            let (layers, seed) = (layers.parse().unwrap(), seed.parse().unwrap());
+            let adversarial = sif::encode_adversarial(&target, WIDTH, HEIGHT, layers, seed)
+                .unwrap_or_else(|e| panic!("{}", e));
+            println!("{}", adversarial.encode());
         }
-        println!("");
+        ["--export", path, scale] | ["--export", path, scale, _] => {
+            let mode = match args.get(3) {
+                None | Some(&"over") => palette::BlendMode::Over,
+                Some(&"additive") => palette::BlendMode::Additive,
+                Some(mode) => panic!("unknown blend mode {}, expected over or additive", mode),
+            };
+            let picture = palette::composite(&image, &palette::Palette::sif(), mode)
+                .unwrap_or_else(|e| panic!("{}", e));
+            let file = BufWriter::new(File::create(path).unwrap());
+            let scale = scale.parse().unwrap();
+            match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+                Some("png") => picture.write_png(file, scale).unwrap(),
+                Some("ppm") => {
+                    let mut file = file;
+                    picture.write_ppm(&mut file, scale, [40, 40, 56]).unwrap()
+                }
+                _ => panic!("unknown image format {}, expected png or ppm", path),
+            }
+        }
+        _ => panic!(
+            "usage: day08 [--stream file|- width height | --histogram | --layer n | --stack n | --deciding | --optimize | --adversarial layers seed | --export file.(png|ppm) scale [over|additive]]"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_day08_part2() {
+        let image = SpaceImage::parse(include_str!("input.txt"), WIDTH, HEIGHT).unwrap();
+        assert_eq!(image.checksum(), Some(2318));
+        assert_eq!(
+            read_image(WIDTH, HEIGHT, &image.compose()),
+            Ok("AHFCB".to_string())
+        );
     }
 }
@@ -0,0 +1,205 @@
+use std::fmt;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+/// Glyph width plus one empty column between letters.
+const GLYPH_PITCH: usize = GLYPH_WIDTH + 1;
+
+/// The block letter font used by the puzzles, `#` marks lit pixels.
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Monochrome image, `true` for lit pixels.
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    pub fn from_fn(width: usize, height: usize, lit: impl Fn(usize, usize) -> bool) -> Bitmap {
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                pixels.push(lit(x, y));
+            }
+        }
+        Bitmap {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && self.pixels[x + y * self.width]
+    }
+
+    /// Glyph cell starting at column `x0` packed into bits, row by row.
+    fn cell(&self, x0: usize) -> u32 {
+        let mut bits = 0;
+        for y in 0..GLYPH_HEIGHT {
+            for x in 0..GLYPH_WIDTH {
+                bits = bits << 1 | self.get(x0 + x, y) as u32;
+            }
+        }
+        bits
+    }
+}
+
+fn glyph_bits(rows: &[&str; GLYPH_HEIGHT]) -> u32 {
+    rows.iter()
+        .flat_map(|row| row.bytes())
+        .fold(0, |bits, c| bits << 1 | (c == b'#') as u32)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownGlyph {
+    /// Position of the glyph in the text.
+    pub index: usize,
+    /// Leftmost column of the glyph in the bitmap.
+    pub x: usize,
+    /// The glyph's pixels in the same notation as the font.
+    pub rows: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OcrError {
+    WrongHeight(usize),
+    /// `text` has a `?` for each unknown glyph.
+    UnknownGlyphs {
+        text: String,
+        glyphs: Vec<UnknownGlyph>,
+    },
+}
+
+impl fmt::Display for OcrError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OcrError::WrongHeight(height) => write!(
+                f,
+                "image is {} pixels high, letters are {}",
+                height, GLYPH_HEIGHT
+            ),
+            OcrError::UnknownGlyphs { text, glyphs } => {
+                write!(f, "unknown glyphs in \"{}\"", text)?;
+                for glyph in glyphs {
+                    write!(
+                        f,
+                        "\nglyph {} at column {}:\n{}",
+                        glyph.index,
+                        glyph.x,
+                        glyph.rows.join("\n")
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Reads the letters of a bitmap whose first glyph starts at column 0.
+/// Letters are spaced one column apart, the last one may touch the right edge.
+pub fn recognize(bitmap: &Bitmap) -> Result<String, OcrError> {
+    if bitmap.height != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight(bitmap.height));
+    }
+
+    let mut text = String::new();
+    let mut unknown = Vec::new();
+    for (index, x) in (0..bitmap.width).step_by(GLYPH_PITCH).enumerate() {
+        let bits = bitmap.cell(x);
+        match FONT.iter().find(|(_, rows)| glyph_bits(rows) == bits) {
+            Some((c, _)) => text.push(*c),
+            None => {
+                text.push('?');
+                unknown.push(UnknownGlyph {
+                    index,
+                    x,
+                    rows: (0..GLYPH_HEIGHT)
+                        .map(|y| {
+                            (x..(x + GLYPH_WIDTH))
+                                .map(|x| if bitmap.get(x, y) { '#' } else { '.' })
+                                .collect()
+                        })
+                        .collect(),
+                });
+            }
+        }
+    }
+
+    if unknown.is_empty() {
+        Ok(text)
+    } else {
+        Err(OcrError::UnknownGlyphs {
+            text,
+            glyphs: unknown,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bitmap(rows: &[&str]) -> Bitmap {
+        Bitmap::from_fn(rows[0].len(), rows.len(), |x, y| {
+            rows[y].as_bytes()[x] == b'#'
+        })
+    }
+
+    #[test]
+    fn font_roundtrip() {
+        for (c, rows) in FONT.iter() {
+            assert_eq!(recognize(&bitmap(rows)), Ok(c.to_string()));
+        }
+    }
+
+    #[test]
+    fn unknown_glyphs() {
+        let image = bitmap(&[
+            "#..#.#..#.####",
+            "#..#.#..#.#...",
+            "####.#..#.###.",
+            "#..#.#..#.#...",
+            "#..#.#..#.#...",
+            "#..#.####.####",
+        ]);
+        let error = recognize(&image).unwrap_err();
+        assert_eq!(
+            error,
+            OcrError::UnknownGlyphs {
+                text: "H?E".to_string(),
+                glyphs: vec![UnknownGlyph {
+                    index: 1,
+                    x: 5,
+                    rows: vec!["#..#", "#..#", "#..#", "#..#", "#..#", "####"]
+                        .into_iter()
+                        .map(String::from)
+                        .collect(),
+                }],
+            }
+        );
+        assert!(error
+            .to_string()
+            .starts_with("unknown glyphs in \"H?E\"\nglyph 1 at column 5:\n#..#\n"));
+        assert_eq!(recognize(&bitmap(&["#..#"])), Err(OcrError::WrongHeight(1)));
+    }
+}
@@ -0,0 +1,265 @@
+use crate::sif::SpaceImage;
+use std::fmt;
+use std::io;
+use std::io::Write;
+
+/// Digits can only address ten colors.
+pub const MAX_COLORS: usize = 10;
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum PaletteError {
+    TooManyColors(usize),
+    TransparentOutOfRange(u8),
+    /// The image uses a digit the palette has no color for.
+    UnknownColor {
+        offset: usize,
+        digit: u8,
+    },
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PaletteError::TooManyColors(count) => {
+                write!(f, "{} colors, a palette has at most {}", count, MAX_COLORS)
+            }
+            PaletteError::TransparentOutOfRange(index) => {
+                write!(f, "transparent index {} is not in the palette", index)
+            }
+            PaletteError::UnknownColor { offset, digit } => {
+                write!(f, "offset {}: no color for digit {}", offset, digit)
+            }
+        }
+    }
+}
+
+/// RGBA colors indexed by the image digits. Transparent indices are skipped entirely,
+/// other colors are blended according to their alpha.
+#[derive(Clone)]
+pub struct Palette {
+    pub colors: Vec<[u8; 4]>,
+    pub transparent: Vec<u8>,
+}
+
+impl Palette {
+    #[allow(dead_code)]
+    pub fn new(colors: Vec<[u8; 4]>, transparent: &[u8]) -> Result<Palette, PaletteError> {
+        if colors.len() > MAX_COLORS {
+            return Err(PaletteError::TooManyColors(colors.len()));
+        }
+        if let Some(index) = transparent.iter().find(|i| **i as usize >= colors.len()) {
+            return Err(PaletteError::TransparentOutOfRange(*index));
+        }
+        Ok(Palette {
+            colors,
+            transparent: transparent.to_vec(),
+        })
+    }
+
+    /// The puzzle's palette: 0 black, 1 white, 2 transparent.
+    pub fn sif() -> Palette {
+        Palette {
+            colors: vec![[0, 0, 0, 255], [255, 255, 255, 255], [0, 0, 0, 0]],
+            transparent: vec![2],
+        }
+    }
+
+    pub fn is_transparent(&self, digit: u8) -> bool {
+        self.transparent.contains(&digit)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Porter-Duff "over", upper layers cover lower ones according to their alpha.
+    Over,
+    /// Colors add up, weighted by their alpha.
+    Additive,
+}
+
+fn blend(mode: BlendMode, src: [u8; 4], dst: [u8; 4]) -> [u8; 4] {
+    let sa = src[3] as f32 / 255.0;
+    let da = dst[3] as f32 / 255.0;
+    let mut out = [0; 4];
+    match mode {
+        BlendMode::Over => {
+            let a = sa + da * (1.0 - sa);
+            for i in 0..3 {
+                if a > 0.0 {
+                    let c = (src[i] as f32 * sa + dst[i] as f32 * da * (1.0 - sa)) / a;
+                    out[i] = c.round() as u8;
+                }
+            }
+            out[3] = (a * 255.0).round() as u8;
+        }
+        BlendMode::Additive => {
+            for i in 0..3 {
+                out[i] = (dst[i] as f32 + src[i] as f32 * sa).round().min(255.0) as u8;
+            }
+            out[3] = dst[3].saturating_add(src[3]);
+        }
+    }
+    out
+}
+
+/// Composited image, one RGBA value per pixel.
+pub struct Picture {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+/// Blends the layers bottom up, starting from fully transparent black.
+pub fn composite(
+    image: &SpaceImage,
+    palette: &Palette,
+    mode: BlendMode,
+) -> Result<Picture, PaletteError> {
+    let mut pixels = vec![[0; 4]; image.layer_size()];
+    for (l, layer) in image.layers().enumerate().rev() {
+        for (i, (pixel, digit)) in pixels.iter_mut().zip(layer.iter()).enumerate() {
+            if palette.is_transparent(*digit) {
+                continue;
+            }
+            let color = palette
+                .colors
+                .get(*digit as usize)
+                .ok_or(PaletteError::UnknownColor {
+                    offset: i + l * image.layer_size(),
+                    digit: *digit,
+                })?;
+            *pixel = blend(mode, *color, *pixel);
+        }
+    }
+    Ok(Picture {
+        width: image.width,
+        height: image.height,
+        pixels,
+    })
+}
+
+impl Picture {
+    /// Pixels enlarged to `scale`×`scale` squares, row by row.
+    fn scaled(&self, scale: usize) -> impl Iterator<Item = &[u8; 4]> {
+        (0..self.height * scale).flat_map(move |y| {
+            (0..self.width * scale).map(move |x| &self.pixels[x / scale + (y / scale) * self.width])
+        })
+    }
+
+    pub fn write_png(&self, out: impl Write, scale: usize) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(
+            out,
+            (self.width * scale) as u32,
+            (self.height * scale) as u32,
+        );
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = self.scaled(scale).flatten().copied().collect();
+        encoder.write_header()?.write_image_data(&data)
+    }
+
+    /// PPM has no alpha channel, so the picture is put over an opaque `background`.
+    pub fn write_ppm(
+        &self,
+        out: &mut impl Write,
+        scale: usize,
+        background: [u8; 3],
+    ) -> io::Result<()> {
+        write!(
+            out,
+            "P6\n{} {}\n255\n",
+            self.width * scale,
+            self.height * scale
+        )?;
+        let [r, g, b] = background;
+        for pixel in self.scaled(scale) {
+            let c = blend(BlendMode::Over, *pixel, [r, g, b, 255]);
+            out.write_all(&c[..3])?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sif::TRANSPARENT;
+
+    #[test]
+    fn sif_palette_matches_compose() {
+        let image = SpaceImage::parse(include_str!("input.txt"), 25, 6).unwrap();
+        let picture = composite(&image, &Palette::sif(), BlendMode::Over).unwrap();
+        let colors = Palette::sif().colors;
+        for (pixel, digit) in picture.pixels.iter().zip(image.compose()) {
+            assert_ne!(digit, TRANSPARENT);
+            assert_eq!(*pixel, colors[digit as usize]);
+        }
+    }
+
+    #[test]
+    fn blending() {
+        let palette = Palette::new(
+            vec![
+                [0, 0, 0, 0],
+                [255, 0, 0, 255],
+                [0, 0, 255, 128],
+                [0, 255, 0, 255],
+            ],
+            &[0],
+        )
+        .unwrap();
+        // Pixels: half blue over red, transparent over green, red over green.
+        let image = SpaceImage::parse("201133", 3, 1).unwrap();
+        let over = composite(&image, &palette, BlendMode::Over).unwrap();
+        assert_eq!(
+            over.pixels,
+            [[127, 0, 128, 255], [0, 255, 0, 255], [255, 0, 0, 255]]
+        );
+        let additive = composite(&image, &palette, BlendMode::Additive).unwrap();
+        assert_eq!(
+            additive.pixels,
+            [[255, 0, 128, 255], [0, 255, 0, 255], [255, 255, 0, 255]]
+        );
+
+        let image = SpaceImage::parse("04", 2, 1).unwrap();
+        assert_eq!(
+            composite(&image, &palette, BlendMode::Over).err(),
+            Some(PaletteError::UnknownColor {
+                offset: 1,
+                digit: 4
+            })
+        );
+        assert_eq!(
+            Palette::new(vec![[0; 4]; 11], &[]).err(),
+            Some(PaletteError::TooManyColors(11))
+        );
+        assert_eq!(
+            Palette::new(vec![[0; 4]; 2], &[2]).err(),
+            Some(PaletteError::TransparentOutOfRange(2))
+        );
+    }
+
+    #[test]
+    fn export() {
+        let image = SpaceImage::parse("0212", 2, 1).unwrap();
+        let picture = composite(&image, &Palette::sif(), BlendMode::Over).unwrap();
+
+        let mut ppm = Vec::new();
+        picture.write_ppm(&mut ppm, 2, [0, 0, 255]).unwrap();
+        assert!(ppm.starts_with(b"P6\n4 2\n255\n"));
+        assert_eq!(
+            ppm[11..],
+            [0, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 255].repeat(2)[..]
+        );
+
+        let mut png = Vec::new();
+        picture.write_png(&mut png, 2).unwrap();
+        let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (4, 2));
+        assert_eq!(pixels[..8], [0, 0, 0, 255, 0, 0, 0, 255]);
+        assert_eq!(pixels[8..16], [0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,318 @@
+use std::fmt;
+use std::io;
+
+/// Pixel value that lets the layers below show through.
+pub const TRANSPARENT: u8 = 2;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum SifError {
+    EmptyLayer,
+    /// Encoding needs a budget of at least one layer.
+    NoLayers,
+    InvalidDigit {
+        offset: usize,
+        c: char,
+    },
+    /// Whitespace may only trail the digits.
+    DataAfterWhitespace {
+        offset: usize,
+    },
+    /// The data doesn't end at a layer boundary.
+    IncompleteLayer {
+        len: usize,
+        layer_size: usize,
+    },
+    Io(io::ErrorKind),
+}
+
+impl fmt::Display for SifError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SifError::EmptyLayer => write!(f, "width and height need to be at least 1"),
+            SifError::NoLayers => write!(f, "an image needs at least one layer"),
+            SifError::InvalidDigit { offset, c } => {
+                write!(f, "offset {}: expected digit, got `{}`", offset, c)
+            }
+            SifError::DataAfterWhitespace { offset } => {
+                write!(f, "offset {}: data after trailing whitespace", offset)
+            }
+            SifError::IncompleteLayer { len, layer_size } => write!(
+                f,
+                "{} pixels are not a multiple of the layer size {}, last layer has {} pixels",
+                len,
+                layer_size,
+                len % layer_size
+            ),
+            SifError::Io(kind) => write!(f, "read failed: {}", kind),
+        }
+    }
+}
+
+/// Image in the Space Image Format: layers of `width`×`height` digits, top layer first.
+pub struct SpaceImage {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<u8>,
+}
+
+impl SpaceImage {
+    /// Parses the digits, ignoring trailing whitespace.
+    pub fn parse(input: &str, width: usize, height: usize) -> Result<SpaceImage, SifError> {
+        if width == 0 || height == 0 {
+            return Err(SifError::EmptyLayer);
+        }
+        let data = input
+            .trim_end()
+            .chars()
+            .enumerate()
+            .map(|(offset, c)| match c.to_digit(10) {
+                Some(digit) => Ok(digit as u8),
+                None => Err(SifError::InvalidDigit { offset, c }),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if data.len() % (width * height) != 0 {
+            return Err(SifError::IncompleteLayer {
+                len: data.len(),
+                layer_size: width * height,
+            });
+        }
+        Ok(SpaceImage {
+            width,
+            height,
+            data,
+        })
+    }
+
+    pub fn layer_size(&self) -> usize {
+        self.width * self.height
+    }
+
+    pub fn layers(&self) -> std::slice::Chunks<'_, u8> {
+        self.data.chunks(self.layer_size())
+    }
+
+    /// Number of 1 digits times number of 2 digits of the layer with the fewest 0 digits.
+    pub fn checksum(&self) -> Option<usize> {
+        let count = |layer: &[u8], digit| layer.iter().filter(|p| **p == digit).count();
+        self.layers()
+            .min_by_key(|layer| count(layer, 0))
+            .map(|layer| count(layer, 1) * count(layer, 2))
+    }
+
+    /// Flattens the layers, every pixel takes the value of the topmost non transparent layer.
+    /// Pixels that are transparent in every layer stay transparent.
+    pub fn compose(&self) -> Vec<u8> {
+        let mut image = vec![TRANSPARENT; self.layer_size()];
+        for layer in self.layers().rev() {
+            for (pixel, value) in image.iter_mut().zip(layer.iter()) {
+                if *value != TRANSPARENT {
+                    *pixel = *value;
+                }
+            }
+        }
+        image
+    }
+
+    /// Digit stream in the format read by `parse`.
+    pub fn encode(&self) -> String {
+        self.data
+            .iter()
+            .map(|digit| std::char::from_digit(*digit as u32, 10).unwrap())
+            .collect()
+    }
+
+    /// Copy without the layers that don't decide any pixel of the composed image.
+    pub fn optimize(&self) -> SpaceImage {
+        let mut decided = vec![false; self.layer_size()];
+        let mut data = Vec::new();
+        for layer in self.layers() {
+            let mut deciding = false;
+            for (decided, value) in decided.iter_mut().zip(layer.iter()) {
+                if !*decided && *value != TRANSPARENT {
+                    *decided = true;
+                    deciding = true;
+                }
+            }
+            if deciding {
+                data.extend_from_slice(layer);
+            }
+        }
+        SpaceImage {
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+fn check_target(target: &[u8], width: usize, height: usize, layers: usize) -> Result<(), SifError> {
+    if width == 0 || height == 0 {
+        return Err(SifError::EmptyLayer);
+    }
+    if layers == 0 {
+        return Err(SifError::NoLayers);
+    }
+    if target.len() != width * height {
+        return Err(SifError::IncompleteLayer {
+            len: target.len(),
+            layer_size: width * height,
+        });
+    }
+    Ok(())
+}
+
+/// Image with `layers` layers that composes to `target`: the target on top, transparent layers below.
+#[allow(dead_code)]
+pub fn encode(
+    target: &[u8],
+    width: usize,
+    height: usize,
+    layers: usize,
+) -> Result<SpaceImage, SifError> {
+    check_target(target, width, height, layers)?;
+    let mut data = target.to_vec();
+    data.resize(target.len() * layers, TRANSPARENT);
+    Ok(SpaceImage {
+        width,
+        height,
+        data,
+    })
+}
+
+/// Xorshift generator, good enough to shuffle layers around reproducibly.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % bound as u64) as usize
+    }
+}
+
+/// Image with `layers` layers that composes to `target` where each pixel is decided
+/// by a random, preferably deep layer. Everything above the deciding layer is transparent,
+/// everything below it is random noise. Equal seeds give equal images.
+pub fn encode_adversarial(
+    target: &[u8],
+    width: usize,
+    height: usize,
+    layers: usize,
+    seed: u64,
+) -> Result<SpaceImage, SifError> {
+    check_target(target, width, height, layers)?;
+    let mut rng = XorShift(seed.max(1));
+    let mut data = vec![TRANSPARENT; target.len() * layers];
+    for (i, value) in target.iter().enumerate() {
+        if *value == TRANSPARENT {
+            continue;
+        }
+        let deciding = rng.next(layers).max(rng.next(layers));
+        data[i + deciding * target.len()] = *value;
+        for layer in (deciding + 1)..layers {
+            data[i + layer * target.len()] = rng.next(3) as u8;
+        }
+    }
+    Ok(SpaceImage {
+        width,
+        height,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_part1() {
+        let image = SpaceImage::parse("123456789012\n", 3, 2).unwrap();
+        let layers: Vec<_> = image.layers().collect();
+        assert_eq!(layers, [[1, 2, 3, 4, 5, 6], [7, 8, 9, 0, 1, 2]]);
+        assert_eq!(image.checksum(), Some(1));
+        assert_eq!(SpaceImage::parse("", 3, 2).unwrap().checksum(), None);
+    }
+
+    #[test]
+    fn samples_part2() {
+        let image = SpaceImage::parse("0222112222120000", 2, 2).unwrap();
+        assert_eq!(image.compose(), [0, 1, 1, 0]);
+        let image = SpaceImage::parse("2222", 2, 1).unwrap();
+        assert_eq!(image.compose(), [TRANSPARENT, TRANSPARENT]);
+    }
+
+    #[test]
+    fn encode_roundtrip() {
+        let target = [0, 1, 1, 0, TRANSPARENT, 1];
+        let image = encode(&target, 3, 2, 4).unwrap();
+        assert_eq!(image.layers().count(), 4);
+        let decoded = SpaceImage::parse(&image.encode(), 3, 2).unwrap();
+        assert_eq!(decoded.compose(), target);
+        assert_eq!(decoded.optimize().data, target);
+
+        for seed in 0..20 {
+            let image = encode_adversarial(&target, 3, 2, 10, seed).unwrap();
+            let decoded = SpaceImage::parse(&image.encode(), 3, 2).unwrap();
+            assert_eq!(decoded.data, image.data);
+            assert_eq!(decoded.compose(), target);
+            let optimized = decoded.optimize();
+            assert_eq!(optimized.compose(), target);
+            assert!(optimized.layers().count() <= 5);
+        }
+        assert_ne!(
+            encode_adversarial(&target, 3, 2, 10, 1).unwrap().data,
+            encode_adversarial(&target, 3, 2, 10, 2).unwrap().data
+        );
+
+        assert_eq!(encode(&target, 3, 2, 0).err(), Some(SifError::NoLayers));
+        assert_eq!(
+            encode(&target, 2, 2, 1).err(),
+            Some(SifError::IncompleteLayer {
+                len: 6,
+                layer_size: 4
+            })
+        );
+    }
+
+    #[test]
+    fn adversarial_puzzle_image() {
+        let image = SpaceImage::parse(include_str!("input.txt"), 25, 6).unwrap();
+        let target = image.compose();
+        let adversarial = encode_adversarial(&target, 25, 6, 200, 2019).unwrap();
+        assert_eq!(adversarial.compose(), target);
+        let transparent = adversarial
+            .data
+            .iter()
+            .filter(|p| **p == TRANSPARENT)
+            .count();
+        assert!(transparent * 2 > adversarial.data.len());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            SpaceImage::parse("1234567", 3, 2).err(),
+            Some(SifError::IncompleteLayer {
+                len: 7,
+                layer_size: 6
+            })
+        );
+        assert_eq!(
+            SpaceImage::parse("12x4", 2, 2).err(),
+            Some(SifError::InvalidDigit { offset: 2, c: 'x' })
+        );
+        assert_eq!(
+            SpaceImage::parse("", 0, 2).err(),
+            Some(SifError::EmptyLayer)
+        );
+        assert_eq!(
+            SifError::IncompleteLayer {
+                len: 7,
+                layer_size: 6
+            }
+            .to_string(),
+            "7 pixels are not a multiple of the layer size 6, last layer has 1 pixels"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use crate::sif::*;
+use std::io::Read;
+
+/// Everything `SpaceImage` offers about an image that can be computed in a single pass.
+pub struct StreamSummary {
+    pub width: usize,
+    pub height: usize,
+    pub layers: usize,
+    pub checksum: Option<usize>,
+    pub composed: Vec<u8>,
+}
+
+/// Per-layer state, the only buffer besides the composed image.
+struct LayerCounts {
+    pixels: usize,
+    digits: [usize; 10],
+}
+
+/// Decodes a SIF digit stream without keeping more than one layer worth of data around.
+/// Digits may be followed by whitespace only, offsets in errors count bytes.
+pub fn decode(
+    mut reader: impl Read,
+    width: usize,
+    height: usize,
+) -> Result<StreamSummary, SifError> {
+    if width == 0 || height == 0 {
+        return Err(SifError::EmptyLayer);
+    }
+    let layer_size = width * height;
+    let mut summary = StreamSummary {
+        width,
+        height,
+        layers: 0,
+        checksum: None,
+        composed: vec![TRANSPARENT; layer_size],
+    };
+    let mut fewest_zeros = usize::MAX;
+    let mut layer = LayerCounts {
+        pixels: 0,
+        digits: [0; 10],
+    };
+    let mut offset = 0;
+    let mut trailing_whitespace = false;
+    let mut buffer = [0; 64 * 1024];
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(SifError::Io(e.kind())),
+        };
+        for c in buffer[..read].iter() {
+            let digit = match c {
+                _ if c.is_ascii_whitespace() => {
+                    trailing_whitespace = true;
+                    offset += 1;
+                    continue;
+                }
+                _ if trailing_whitespace => return Err(SifError::DataAfterWhitespace { offset }),
+                b'0'..=b'9' => c - b'0',
+                _ => {
+                    return Err(SifError::InvalidDigit {
+                        offset,
+                        c: *c as char,
+                    })
+                }
+            };
+
+            // The top layer comes first, so the first opaque digit decides the pixel.
+            let pixel = &mut summary.composed[layer.pixels];
+            if *pixel == TRANSPARENT {
+                *pixel = digit;
+            }
+            layer.digits[digit as usize] += 1;
+            layer.pixels += 1;
+            if layer.pixels == layer_size {
+                if layer.digits[0] < fewest_zeros {
+                    fewest_zeros = layer.digits[0];
+                    summary.checksum = Some(layer.digits[1] * layer.digits[2]);
+                }
+                summary.layers += 1;
+                layer = LayerCounts {
+                    pixels: 0,
+                    digits: [0; 10],
+                };
+            }
+            offset += 1;
+        }
+    }
+
+    if layer.pixels != 0 {
+        return Err(SifError::IncompleteLayer {
+            len: summary.layers * layer_size + layer.pixels,
+            layer_size,
+        });
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    /// Endless repetition of `pattern`, cut off after `len` bytes.
+    struct Repeat {
+        pattern: &'static [u8],
+        position: usize,
+        len: usize,
+    }
+
+    impl Read for Repeat {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let count = buf.len().min(self.len - self.position);
+            for b in buf[..count].iter_mut() {
+                *b = self.pattern[self.position % self.pattern.len()];
+                self.position += 1;
+            }
+            Ok(count)
+        }
+    }
+
+    struct Failing;
+
+    impl Read for Failing {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "gone"))
+        }
+    }
+
+    #[test]
+    fn matches_space_image() {
+        let input = include_str!("input.txt");
+        let image = SpaceImage::parse(input, 25, 6).unwrap();
+        let summary = decode(input.as_bytes(), 25, 6).unwrap();
+        assert_eq!(summary.layers, image.layers().count());
+        assert_eq!(summary.checksum, image.checksum());
+        assert_eq!(summary.composed, image.compose());
+
+        let summary = decode(&b"0222112222120000\n"[..], 2, 2).unwrap();
+        assert_eq!((summary.layers, summary.composed), (4, vec![0, 1, 1, 0]));
+
+        let summary = decode(&b"\n"[..], 2, 2).unwrap();
+        assert_eq!((summary.layers, summary.checksum), (0, None));
+    }
+
+    #[test]
+    fn large_stream() {
+        // 2.5 million identical layers whose first two pixels stay transparent.
+        let mut reader = Repeat {
+            pattern: b"2210",
+            position: 0,
+            len: 10_000_000,
+        };
+        let summary = decode(&mut reader, 2, 2).unwrap();
+        assert_eq!(summary.layers, 2_500_000);
+        assert_eq!(summary.checksum, Some(2));
+        assert_eq!(summary.composed, [2, 2, 1, 0]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            decode(&b"1234567"[..], 3, 2).err(),
+            Some(SifError::IncompleteLayer {
+                len: 7,
+                layer_size: 6
+            })
+        );
+        assert_eq!(
+            decode(&b"1234\n56"[..], 2, 2).err(),
+            Some(SifError::DataAfterWhitespace { offset: 5 })
+        );
+        assert_eq!(
+            decode(&b"12x4"[..], 2, 2).err(),
+            Some(SifError::InvalidDigit { offset: 2, c: 'x' })
+        );
+        assert_eq!(
+            decode(Failing, 2, 2).err(),
+            Some(SifError::Io(io::ErrorKind::BrokenPipe))
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use crate::disasm::*;
+use crate::intcode::*;
+use crate::isa::InstructionSet;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::rc::Rc;
+
+/// Runs `program` once per test of `suite`, each getting its own inputs, and accumulates the coverage.
+pub fn run_suite(
+    isa: &Rc<InstructionSet>,
+    program: &HashMap<u64, i64>,
+    suite: &[Vec<i64>],
+) -> Coverage {
+    let mut coverage = Coverage::default();
+    for (test, inputs) in suite.iter().enumerate() {
+        let mut state = ProgramState::with_isa(program.clone(), Rc::clone(isa));
+        state.inputs = inputs.iter().copied().collect();
+        state.coverage = Some(Coverage::default());
+        loop {
+            match state.eval_program() {
+                ProgramResult::Output(_) => {}
+                ProgramResult::Halted => break,
+                ProgramResult::WaitForInputAt => {
+                    panic!("test {} needs more input than {:?}", test, inputs)
+                }
+            }
+        }
+        coverage.merge(state.coverage.as_ref().unwrap());
+    }
+    coverage
+}
+
+/// Instruction addresses to report on: everything the listing decodes plus anything that actually ran.
+fn code_addresses(coverage: &Coverage, listing: &[ListingLine]) -> BTreeSet<u64> {
+    listing
+        .iter()
+        .filter(|line| !line.text.starts_with(".data"))
+        .map(|line| line.address)
+        .chain(coverage.hits.keys().copied())
+        .collect()
+}
+
+/// Disassembly of `program` with execution counts in front of every instruction (`#####` if it never ran)
+/// and taken/not taken counts after conditional jumps.
+pub fn annotated_listing(
+    coverage: &Coverage,
+    isa: &InstructionSet,
+    program: &HashMap<u64, i64>,
+) -> String {
+    let listing = disassemble(isa, program);
+    let get = |i| *program.get(&i).unwrap_or(&0);
+    let mut report = String::new();
+    for address in code_addresses(coverage, &listing) {
+        let text = match listing.iter().find(|line| line.address == address) {
+            Some(line) if !line.text.starts_with(".data") => line.text.clone(),
+            _ => match disassemble_at(isa, get, address) {
+                Some(line) => line.text,
+                None => format!(".data {}", get(address)),
+            },
+        };
+        let hits = match coverage.hits.get(&address) {
+            Some(hits) => hits.to_string(),
+            None => "#####".to_string(),
+        };
+        write!(report, "{:>8} {:>6}: {}", hits, address, text).unwrap();
+        if let Some([taken, not_taken]) = coverage.branches.get(&address) {
+            write!(report, "  [taken {}, not taken {}]", taken, not_taken).unwrap();
+        }
+        report.push('\n');
+    }
+
+    let executed = coverage.hits.len();
+    let directions: usize = coverage
+        .branches
+        .values()
+        .map(|d| d.iter().filter(|count| **count > 0).count())
+        .sum();
+    writeln!(
+        report,
+        "instructions {}/{}, branch directions {}/{}",
+        executed,
+        code_addresses(coverage, &listing).len(),
+        directions,
+        branch_addresses(isa, program, coverage).len() * 2
+    )
+    .unwrap();
+    report
+}
+
+fn branch_addresses(
+    isa: &InstructionSet,
+    program: &HashMap<u64, i64>,
+    coverage: &Coverage,
+) -> BTreeSet<u64> {
+    let is_conditional = |address| {
+        let opcode = *program.get(&address).unwrap_or(&0) % 100;
+        isa.get(opcode)
+            .is_some_and(|instruction| instruction.conditional)
+    };
+    disassemble(isa, program)
+        .iter()
+        .filter(|line| !line.text.starts_with(".data") && is_conditional(line.address))
+        .map(|line| line.address)
+        .chain(coverage.branches.keys().copied())
+        .collect()
+}
+
+/// lcov tracefile for `program`, using instruction address + 1 as line number.
+pub fn lcov(
+    coverage: &Coverage,
+    isa: &InstructionSet,
+    program: &HashMap<u64, i64>,
+    source: &str,
+) -> String {
+    let listing = disassemble(isa, program);
+    let mut out = String::new();
+    writeln!(out, "TN:\nSF:{}", source).unwrap();
+
+    let branches = branch_addresses(isa, program, coverage);
+    let mut branches_hit = 0;
+    for address in branches.iter() {
+        let executed = coverage.hits.contains_key(address);
+        let directions = coverage.branches.get(address).copied().unwrap_or([0, 0]);
+        for (block, count) in directions.iter().enumerate() {
+            let taken = if executed {
+                count.to_string()
+            } else {
+                "-".to_string()
+            };
+            writeln!(out, "BRDA:{},0,{},{}", address + 1, block, taken).unwrap();
+            if *count > 0 {
+                branches_hit += 1;
+            }
+        }
+    }
+    writeln!(out, "BRF:{}\nBRH:{}", branches.len() * 2, branches_hit).unwrap();
+
+    let lines = code_addresses(coverage, &listing);
+    for address in lines.iter() {
+        writeln!(
+            out,
+            "DA:{},{}",
+            address + 1,
+            coverage.hits.get(address).unwrap_or(&0)
+        )
+        .unwrap();
+    }
+    writeln!(out, "LF:{}\nLH:{}", lines.len(), coverage.hits.len()).unwrap();
+    out.push_str("end_of_record\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const JUMP_TEST: &str = "3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9";
+
+    /// Runs the day 5 jump test once for every list of inputs.
+    fn jump_suite(suite: &[Vec<i64>]) -> Coverage {
+        run_suite(
+            &Rc::new(InstructionSet::day05()),
+            &parse_program(JUMP_TEST),
+            suite,
+        )
+    }
+
+    #[test]
+    fn annotated_listing_report() {
+        let program = parse_program(JUMP_TEST);
+        let isa = InstructionSet::day05();
+        assert_eq!(
+            annotated_listing(&jump_suite(&[vec![0]]), &isa, &program),
+            "       1      0: in [12]
+       1      2: jz [12], [15]  [taken 1, not taken 0]
+   #####      5: add [13], [14], [13]
+       1      9: out [13]
+       1     11: hlt
+instructions 4/5, branch directions 1/2
+"
+        );
+        assert!(
+            annotated_listing(&jump_suite(&[vec![0], vec![5], vec![7]]), &isa, &program)
+                .ends_with("instructions 5/5, branch directions 2/2\n")
+        );
+    }
+
+    #[test]
+    fn lcov_tracefile() {
+        let program = parse_program(JUMP_TEST);
+        assert_eq!(
+            lcov(
+                &jump_suite(&[vec![0], vec![3]]),
+                &InstructionSet::day05(),
+                &program,
+                "jump.int"
+            ),
+            "TN:
+SF:jump.int
+BRDA:3,0,0,1
+BRDA:3,0,1,1
+BRF:2
+BRH:2
+DA:1,2
+DA:3,2
+DA:6,1
+DA:10,2
+DA:12,2
+LF:5
+LH:5
+end_of_record
+"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "test 1 needs more input than []")]
+    fn suite_without_input() {
+        jump_suite(&[vec![0], vec![]]);
+    }
+}
@@ -0,0 +1,102 @@
+use crate::isa::*;
+use std::collections::HashMap;
+
+pub struct ListingLine {
+    pub address: u64,
+    pub len: u64,
+    pub text: String,
+}
+
+fn format_operand(mode: ParamMode, val: i64) -> String {
+    match mode {
+        ParamMode::Position => format!("[{}]", val),
+        ParamMode::Immediate => val.to_string(),
+        ParamMode::Relative if val < 0 => format!("[rb-{}]", -val),
+        ParamMode::Relative => format!("[rb+{}]", val),
+    }
+}
+
+/// Decodes the instruction at `address`, or `None` if the word there can't start an instruction of `isa`.
+pub fn disassemble_at(
+    isa: &InstructionSet,
+    get: impl Fn(u64) -> i64,
+    address: u64,
+) -> Option<ListingLine> {
+    let word = get(address);
+    if word < 0 {
+        return None;
+    }
+    let instruction = isa.get(word % 100)?;
+    let mut operands = Vec::new();
+    for p in 0..instruction.arity {
+        let mode = ParamMode::from_digit(word / (100 * 10i64.pow(p as u32)) % 10)?;
+        if !isa.allows_mode(mode) || (instruction.is_write(p) && mode == ParamMode::Immediate) {
+            return None;
+        }
+        operands.push(format_operand(mode, get(address + p as u64 + 1)));
+    }
+    if word / (100 * 10i64.pow(instruction.arity as u32)) != 0 {
+        return None;
+    }
+    Some(ListingLine {
+        address,
+        len: 1 + instruction.arity as u64,
+        text: format!("{} {}", instruction.name, operands.join(", "))
+            .trim_end()
+            .to_string(),
+    })
+}
+
+/// Linear sweep over the whole memory image; words that don't decode are listed as data.
+pub fn disassemble(isa: &InstructionSet, memory: &HashMap<u64, i64>) -> Vec<ListingLine> {
+    let end = memory.keys().max().map_or(0, |max| max + 1);
+    let get = |i| *memory.get(&i).unwrap_or(&0);
+    let mut listing = Vec::new();
+    let mut address = 0;
+    while address < end {
+        let line = match disassemble_at(isa, get, address) {
+            Some(line) if line.address + line.len <= end => line,
+            _ => ListingLine {
+                address,
+                len: 1,
+                text: format!(".data {}", get(address)),
+            },
+        };
+        address += line.len;
+        listing.push(line);
+    }
+    listing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse_program;
+
+    #[test]
+    fn listing() {
+        let memory = parse_program("1,9,10,3,2,3,11,0,99,30,40,50");
+        let text: Vec<String> = disassemble(&InstructionSet::day02(), &memory)
+            .iter()
+            .map(|line| format!("{}: {}", line.address, line.text))
+            .collect();
+        assert_eq!(
+            text,
+            [
+                "0: add [9], [10], [3]",
+                "4: mul [3], [11], [0]",
+                "8: hlt",
+                "9: .data 30",
+                "10: .data 40",
+                "11: .data 50",
+            ]
+        );
+
+        let memory = parse_program("109,-1,204,3,1101,2,3,1,99");
+        let text: Vec<String> = disassemble(&InstructionSet::day09(), &memory)
+            .iter()
+            .map(|line| line.text.clone())
+            .collect();
+        assert_eq!(text, ["arb -1", "out [rb+3]", "add 2, 3, [1]", "hlt"]);
+    }
+}
@@ -0,0 +1,302 @@
+use crate::isa::*;
+use crate::parser::parse_intcode;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::rc::Rc;
+
+pub enum ProgramResult {
+    WaitForInputAt,
+    Output(i64),
+    Halted,
+}
+
+/// Host device that answers reads and writes to a mapped address range.
+/// Addresses are passed as offsets relative to the start of the range.
+pub trait Peripheral {
+    fn read(&mut self, offset: u64) -> i64;
+    fn write(&mut self, offset: u64, val: i64);
+}
+
+#[allow(dead_code)]
+/// Peripheral made from a pair of host callbacks.
+pub struct Hooks<R, W> {
+    pub read: R,
+    pub write: W,
+}
+
+impl<R: FnMut(u64) -> i64, W: FnMut(u64, i64)> Peripheral for Hooks<R, W> {
+    fn read(&mut self, offset: u64) -> i64 {
+        (self.read)(offset)
+    }
+    fn write(&mut self, offset: u64, val: i64) {
+        (self.write)(offset, val)
+    }
+}
+
+struct MappedPeripheral {
+    range: Range<u64>,
+    // Reads go through `get(&self)`, so devices with state need interior mutability.
+    device: RefCell<Box<dyn Peripheral>>,
+}
+
+/// A write to a watched address, `pc` is the address of the writing instruction.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WatchEvent {
+    pub pc: u64,
+    pub address: u64,
+    pub value: i64,
+}
+
+/// Execution counts per instruction address, for conditional jumps also split into taken and not taken.
+#[derive(Default)]
+pub struct Coverage {
+    pub hits: BTreeMap<u64, u64>,
+    pub branches: BTreeMap<u64, [u64; 2]>,
+}
+
+impl Coverage {
+    fn record(&mut self, pc: u64, instruction: &Instruction, step: &Step) {
+        *self.hits.entry(pc).or_insert(0) += 1;
+        if instruction.conditional {
+            let directions = self.branches.entry(pc).or_insert([0, 0]);
+            match step {
+                Step::Jump(_) => directions[0] += 1,
+                _ => directions[1] += 1,
+            }
+        }
+    }
+
+    /// Adds the counts of another run, e.g. to accumulate coverage over a test suite.
+    pub fn merge(&mut self, other: &Coverage) {
+        for (pc, hits) in other.hits.iter() {
+            *self.hits.entry(*pc).or_insert(0) += hits;
+        }
+        for (pc, directions) in other.branches.iter() {
+            let own = self.branches.entry(*pc).or_insert([0, 0]);
+            own[0] += directions[0];
+            own[1] += directions[1];
+        }
+    }
+}
+
+pub struct ProgramState {
+    pub program: HashMap<u64, i64>,
+    pub inputs: VecDeque<i64>,
+    pub pc: u64,
+    pub relative_base: i64,
+    peripherals: Vec<MappedPeripheral>,
+    isa: Rc<InstructionSet>,
+    watches: Vec<Range<u64>>,
+    pub watch_log: Vec<WatchEvent>,
+    /// Collects coverage while set.
+    pub coverage: Option<Coverage>,
+}
+
+impl ProgramState {
+    pub fn new(program: HashMap<u64, i64>) -> ProgramState {
+        ProgramState::with_isa(program, Rc::new(InstructionSet::day09()))
+    }
+
+    pub fn with_isa(program: HashMap<u64, i64>, isa: Rc<InstructionSet>) -> ProgramState {
+        ProgramState {
+            program,
+            inputs: VecDeque::new(),
+            pc: 0,
+            relative_base: 0,
+            peripherals: Vec::new(),
+            isa,
+            watches: Vec::new(),
+            watch_log: Vec::new(),
+            coverage: None,
+        }
+    }
+
+    /// Records every following write into `range` in `watch_log`.
+    #[allow(dead_code)]
+    pub fn watch(&mut self, range: Range<u64>) {
+        self.watches.push(range);
+    }
+
+    #[allow(dead_code)]
+    /// Routes all reads and writes within `range` to `device` instead of program memory.
+    pub fn map_peripheral(&mut self, range: Range<u64>, device: Box<dyn Peripheral>) {
+        assert!(range.start < range.end, "empty peripheral range");
+        if let Some(other) = self
+            .peripherals
+            .iter()
+            .find(|p| p.range.start < range.end && range.start < p.range.end)
+        {
+            panic!(
+                "peripheral range {:?} overlaps already mapped range {:?}",
+                range, other.range
+            );
+        }
+        self.peripherals.push(MappedPeripheral {
+            range,
+            device: RefCell::new(device),
+        });
+    }
+
+    fn peripheral_at(&self, i: u64) -> Option<&MappedPeripheral> {
+        self.peripherals.iter().find(|p| p.range.contains(&i))
+    }
+
+    pub fn get(&self, i: u64) -> i64 {
+        match self.peripheral_at(i) {
+            Some(p) => p.device.borrow_mut().read(i - p.range.start),
+            None => *self.program.get(&i).unwrap_or(&0),
+        }
+    }
+    pub fn set(&mut self, i: u64, val: i64) {
+        if self.watches.iter().any(|w| w.contains(&i)) {
+            self.watch_log.push(WatchEvent {
+                pc: self.pc,
+                address: i,
+                value: val,
+            });
+        }
+        match self.peripheral_at(i) {
+            Some(p) => p.device.borrow_mut().write(i - p.range.start, val),
+            None => {
+                self.program.insert(i, val);
+            }
+        }
+    }
+
+    fn param_mode(&self, p: u64) -> ParamMode {
+        let digit = self.get(self.pc) / (100 * 10i64.pow(p as u32)) % 10;
+        match ParamMode::from_digit(digit) {
+            Some(pmode) if self.isa.allows_mode(pmode) => pmode,
+            _ => panic!(
+                "parameter mode {} not supported by {} at {}",
+                digit, self.isa.name, self.pc
+            ),
+        }
+    }
+
+    fn get_param_address(&self, p: u64) -> u64 {
+        match self.param_mode(p) {
+            ParamMode::Position => self.get(self.pc + p + 1) as u64,
+            ParamMode::Immediate => self.pc + p + 1,
+            ParamMode::Relative => (self.get(self.pc + p + 1) + self.relative_base) as u64,
+        }
+    }
+
+    fn get_param(&self, p: u64) -> i64 {
+        self.get(self.get_param_address(p))
+    }
+
+    pub fn eval_program(&mut self) -> ProgramResult {
+        let isa = Rc::clone(&self.isa);
+        loop {
+            let opcode = self.get(self.pc) % 100;
+            let instruction = match isa.get(opcode) {
+                Some(instruction) => instruction,
+                None => panic!("Invalid opcode {} at {}!", opcode, self.pc),
+            };
+            let operands: Vec<i64> = (0..instruction.arity)
+                .map(|p| {
+                    if instruction.is_write(p) {
+                        if self.param_mode(p as u64) == ParamMode::Immediate {
+                            panic!(
+                                "write operand {} of {} in immediate mode at {}",
+                                p, instruction.name, self.pc
+                            );
+                        }
+                        self.get_param_address(p as u64) as i64
+                    } else {
+                        self.get_param(p as u64)
+                    }
+                })
+                .collect();
+
+            let step = (instruction.exec)(self, &operands);
+            if let Some(coverage) = self.coverage.as_mut() {
+                // Waiting instructions are executed again once input arrives.
+                if !matches!(step, Step::WaitForInput) {
+                    coverage.record(self.pc, instruction, &step);
+                }
+            }
+            match step {
+                Step::Next => self.pc += 1 + instruction.arity as u64,
+                Step::Jump(target) => self.pc = target,
+                Step::Output(output) => {
+                    self.pc += 1 + instruction.arity as u64;
+                    return ProgramResult::Output(output);
+                }
+                Step::WaitForInput => return ProgramResult::WaitForInputAt,
+                Step::Halt => return ProgramResult::Halted,
+            }
+        }
+    }
+}
+
+pub fn load_program(values: &[i64]) -> HashMap<u64, i64> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, val)| (i as u64, *val))
+        .collect()
+}
+
+pub fn parse_program(puzzle_input: &str) -> HashMap<u64, i64> {
+    match parse_intcode(puzzle_input) {
+        Ok(values) => load_program(&values),
+        Err(err) => panic!("invalid program: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    fn run_to_halt(state: &mut ProgramState) -> Vec<i64> {
+        let mut output = Vec::new();
+        loop {
+            match state.eval_program() {
+                ProgramResult::Output(out) => output.push(out),
+                ProgramResult::Halted => return output,
+                _ => panic!("invalid program state"),
+            }
+        }
+    }
+
+    #[test]
+    fn peripheral_read_hook() {
+        let mut state = ProgramState::new(parse_program("4,1000,4,1000,4,1001,99"));
+        let counter = Cell::new(0);
+        state.map_peripheral(
+            1000..1002,
+            Box::new(Hooks {
+                read: move |offset| {
+                    counter.set(counter.get() + 1);
+                    counter.get() * 10 + offset as i64
+                },
+                write: |_, _| {},
+            }),
+        );
+        assert_eq!(run_to_halt(&mut state), [10, 20, 31]);
+    }
+
+    #[test]
+    fn peripheral_write_hook() {
+        let mut state = ProgramState::new(parse_program("1101,7,8,2001,99"));
+        let framebuffer = Rc::new(RefCell::new(vec![0; 4]));
+        let device_framebuffer = Rc::clone(&framebuffer);
+        state.map_peripheral(
+            2000..2004,
+            Box::new(Hooks {
+                read: |_| 0,
+                write: move |offset, val| device_framebuffer.borrow_mut()[offset as usize] = val,
+            }),
+        );
+        run_to_halt(&mut state);
+        assert_eq!(*framebuffer.borrow(), [0, 15, 0, 0]);
+        assert_eq!(state.program.get(&2001), None);
+    }
+}
@@ -1,139 +1,119 @@
-use std::collections::HashMap;
-use std::collections::VecDeque;
-
-enum ProgramResult {
-    WaitForInputAt,
-    Output(i64),
-    Halted,
-}
-
-struct ProgramState {
-    program: HashMap<u64, i64>,
-    inputs: VecDeque<i64>,
-    pc: u64,
-    relative_base: i64,
-}
+mod coverage;
+mod disasm;
+mod intcode;
+mod isa;
+mod memdiff;
+mod parser;
+mod validate;
 
-impl ProgramState {
-    fn get(&self, i: u64) -> i64 {
-        *self.program.get(&i).unwrap_or(&0)
-    }
-    fn set(&mut self, i: u64, val: i64) {
-        self.program.insert(i, val);
-    }
-
-    fn get_param_address(&self, p: u64) -> u64 {
-        let pmode = self.get(self.pc) / (100 * 10i64.pow(p as u32)) % 10;
-        match pmode {
-            0 => self.get(self.pc + p + 1) as u64, // position mode
-            1 => self.pc + p + 1,                  // value mode
-            2 => (self.get(self.pc + p + 1) + self.relative_base) as u64, // relative mode
-            _ => panic!("unknown pmode"),
-        }
-    }
-
-    fn get_param(&self, p: u64) -> i64 {
-        self.get(self.get_param_address(p))
-    }
+use intcode::*;
+use isa::InstructionSet;
+use std::collections::HashMap;
+use std::rc::Rc;
 
-    fn eval_program(&mut self) -> ProgramResult {
-        loop {
-            let opcode = self.program[&self.pc] % 100;
-            match opcode {
-                1 => self.set(
-                    self.get_param_address(2),
-                    self.get_param(0) + self.get_param(1),
-                ),
-                2 => self.set(
-                    self.get_param_address(2),
-                    self.get_param(0) * self.get_param(1),
-                ),
-                3 => {
-                    let val = match self.inputs.pop_front() {
-                        Some(input) => input,
-                        None => return ProgramResult::WaitForInputAt,
-                    };
-                    self.set(self.get_param_address(0), val);
-                }
-                4 => {
-                    let output = self.get_param(0);
-                    self.pc += 2;
-                    return ProgramResult::Output(output);
-                }
-                5 => {
-                    if self.get_param(0) != 0 {
-                        self.pc = self.get_param(1) as u64;
-                        continue;
-                    }
-                }
-                6 => {
-                    if self.get_param(0) == 0 {
-                        self.pc = self.get_param(1) as u64;
-                        continue;
-                    }
-                }
-                7 => self.set(
-                    self.get_param_address(2),
-                    if self.get_param(0) < self.get_param(1) {
-                        1
-                    } else {
-                        0
-                    },
-                ),
-                8 => self.set(
-                    self.get_param_address(2),
-                    if self.get_param(0) == self.get_param(1) {
-                        1
-                    } else {
-                        0
-                    },
-                ),
-                9 => self.relative_base += self.get_param(0),
-                99 => return ProgramResult::Halted,
-                _ => panic!("Invalid opcode!"),
-            };
-            self.pc += match opcode {
-                1 => 4,
-                2 => 4,
-                3 => 2,
-                4 => 2,
-                5 => 3,
-                6 => 3,
-                7 => 4,
-                8 => 4,
-                9 => 2,
-                _ => panic!("Invalid opcode!"),
-            };
+/// Loads a program stored as text, hex (`.hex`) or binary (`.icb`) and checks it against `isa`.
+fn load_program_file(path: &str, isa: &InstructionSet) -> HashMap<u64, i64> {
+    let values = if path.ends_with(".icb") {
+        let bytes = std::fs::read(path).unwrap();
+        parser::decode_binary(&bytes).map_err(|err| err.to_string())
+    } else {
+        let text = std::fs::read_to_string(path).unwrap();
+        if path.ends_with(".hex") {
+            parser::decode_hex(&text).map_err(|err| err.to_string())
+        } else {
+            parser::parse_intcode(&text).map_err(|err| err.to_string())
         }
-    }
-}
-
-fn parse_program(puzzle_input: &str) -> HashMap<u64, i64> {
-    let mut program = HashMap::new();
-    for (i, instr) in puzzle_input.split(',').enumerate() {
-        program.insert(i as u64, instr.parse::<i64>().unwrap());
+    };
+    let program = match values {
+        Ok(values) => load_program(&values),
+        Err(err) => panic!("{}: {}", path, err),
+    };
+    if let Err(err) = validate::validate(isa, &program) {
+        panic!("{}: not a {} program, {}", path, isa.name, err);
     }
     program
 }
 
 fn run_program(puzzle_input: &str, input: &[i64]) -> Vec<i64> {
-    let mut program = ProgramState {
-        program: parse_program(puzzle_input),
-        inputs: input.into_iter().map(|i| *i).collect(),
-        pc: 0,
-        relative_base: 0,
-    };
+    let isa = Rc::new(InstructionSet::day09());
+    run_to_halt(parse_program(puzzle_input), &isa, input).0
+}
+
+fn run_to_halt(
+    program: HashMap<u64, i64>,
+    isa: &Rc<InstructionSet>,
+    input: &[i64],
+) -> (Vec<i64>, ProgramState) {
+    let mut program = ProgramState::with_isa(program, Rc::clone(isa));
+    program.inputs = input.iter().copied().collect();
 
     let mut output = Vec::new();
     loop {
         match program.eval_program() {
             ProgramResult::Output(out) => output.push(out),
-            ProgramResult::Halted => return output,
+            ProgramResult::Halted => return (output, program),
             _ => panic!("invalid program state"),
         }
     }
 }
 
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let isa = match args[..] {
+        ["--dialect", name, ..] => {
+            let isa = InstructionSet::dialect(name).unwrap_or_else(|| {
+                panic!("unknown dialect {}, expected day02, day05 or day09", name)
+            });
+            args.drain(..2);
+            isa
+        }
+        _ => InstructionSet::day09(),
+    };
+    let isa = Rc::new(isa);
+    match args[..] {
+        [] => println!("{:?}", run_program(include_str!("input.txt"), &[2])),
+        ["--disasm", path] => {
+            for line in disasm::disassemble(&isa, &load_program_file(path, &isa)) {
+                println!("{:>6}: {}", line.address, line.text);
+            }
+        }
+        ["--diff", before, after] => {
+            let (_, before) = run_to_halt(load_program_file(before, &isa), &isa, &[2]);
+            let (_, after) = run_to_halt(load_program_file(after, &isa), &isa, &[2]);
+            print!(
+                "{}",
+                memdiff::diff_report(&isa, &before.program, &after.program)
+            );
+        }
+        ["--coverage", path, ..] | ["--lcov", path, ..] => {
+            let program = load_program_file(path, &isa);
+            // Every further argument holds the comma separated inputs of one test.
+            let suite: Vec<Vec<i64>> = match args[2..] {
+                [] => vec![vec![2]],
+                ref tests => tests
+                    .iter()
+                    .map(|test| {
+                        parser::parse_intcode(test)
+                            .unwrap_or_else(|err| panic!("test inputs `{}`: {}", test, err))
+                    })
+                    .collect(),
+            };
+            let coverage = coverage::run_suite(&isa, &program, &suite);
+            if args[0] == "--lcov" {
+                print!("{}", coverage::lcov(&coverage, &isa, &program, path));
+            } else {
+                print!("{}", coverage::annotated_listing(&coverage, &isa, &program));
+            }
+        }
+        [path] => println!(
+            "{:?}",
+            run_to_halt(load_program_file(path, &isa), &isa, &[2]).0
+        ),
+        _ => panic!("usage: day09 [--dialect day02|day05|day09] [program | --disasm program | --diff program program | --coverage program [inputs...] | --lcov program [inputs...]]"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -158,8 +138,3 @@ mod tests {
     #[test]
     fn samples_day09_part2() {}
 }
-
-fn main() {
-    let puzzle_input = include_str!("input.txt");
-    println!("{:?}", run_program(puzzle_input, &[2]))
-}
@@ -0,0 +1,174 @@
+use crate::disasm::*;
+use crate::isa::InstructionSet;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Run of consecutive addresses whose values differ between two memory images.
+#[derive(Debug, PartialEq, Eq)]
+pub struct DiffRange {
+    pub start: u64,
+    pub before: Vec<i64>,
+    pub after: Vec<i64>,
+}
+
+impl DiffRange {
+    pub fn end(&self) -> u64 {
+        self.start + self.before.len() as u64
+    }
+}
+
+/// Compares two memory images, unset addresses count as 0.
+pub fn diff_memory(before: &HashMap<u64, i64>, after: &HashMap<u64, i64>) -> Vec<DiffRange> {
+    let addresses: BTreeSet<u64> = before.keys().chain(after.keys()).copied().collect();
+    let mut ranges: Vec<DiffRange> = Vec::new();
+    for address in addresses {
+        let old = *before.get(&address).unwrap_or(&0);
+        let new = *after.get(&address).unwrap_or(&0);
+        if old == new {
+            continue;
+        }
+        match ranges.last_mut() {
+            Some(range) if range.end() == address => {
+                range.before.push(old);
+                range.after.push(new);
+            }
+            _ => ranges.push(DiffRange {
+                start: address,
+                before: vec![old],
+                after: vec![new],
+            }),
+        }
+    }
+    ranges
+}
+
+/// Lists every changed range together with the disassembly of the affected instructions in both images.
+pub fn diff_report(
+    isa: &InstructionSet,
+    before: &HashMap<u64, i64>,
+    after: &HashMap<u64, i64>,
+) -> String {
+    let listing_before = disassemble(isa, before);
+    let listing_after = disassemble(isa, after);
+    let mut report = String::new();
+    for range in diff_memory(before, after) {
+        writeln!(
+            report,
+            "{}..{}: {:?} -> {:?}",
+            range.start,
+            range.end(),
+            range.before,
+            range.after
+        )
+        .unwrap();
+        for (label, listing) in [("-", &listing_before), ("+", &listing_after)].iter() {
+            for line in listing
+                .iter()
+                .filter(|line| line.address < range.end() && range.start < line.address + line.len)
+            {
+                writeln!(report, "  {} {:>5}: {}", label, line.address, line.text).unwrap();
+            }
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::*;
+    use std::rc::Rc;
+
+    fn run_day02(noun: i64, verb: i64) -> ProgramState {
+        let mut state = ProgramState::with_isa(
+            parse_program("1,9,10,3,2,3,11,0,99,30,40,50"),
+            Rc::new(InstructionSet::day02()),
+        );
+        state.set(1, noun);
+        state.set(2, verb);
+        state.watch(0..1);
+        state.watch(3..4);
+        match state.eval_program() {
+            ProgramResult::Halted => state,
+            _ => panic!("invalid program state"),
+        }
+    }
+
+    #[test]
+    fn diff_ranges() {
+        let before = load_program(&[1, 2, 3, 4, 5, 6]);
+        let mut after = load_program(&[1, 0, 0, 4, 5, 7]);
+        after.insert(9, 1);
+        assert_eq!(
+            diff_memory(&before, &after),
+            [
+                DiffRange {
+                    start: 1,
+                    before: vec![2, 3],
+                    after: vec![0, 0]
+                },
+                DiffRange {
+                    start: 5,
+                    before: vec![6],
+                    after: vec![7]
+                },
+                DiffRange {
+                    start: 9,
+                    before: vec![0],
+                    after: vec![1]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn day02_patches() {
+        let original = run_day02(9, 10);
+        let patched = run_day02(11, 10);
+        assert_eq!(
+            diff_report(
+                &InstructionSet::day02(),
+                &original.program,
+                &patched.program
+            ),
+            "0..2: [3500, 9] -> [4500, 11]
+  -     0: .data 3500
+  -     1: .data 9
+  +     0: .data 4500
+  +     1: .data 11
+3..4: [70] -> [90]
+  -     3: .data 70
+  +     3: .data 90
+"
+        );
+        assert!(diff_report(
+            &InstructionSet::day02(),
+            &parse_program("1,9,10,3,2,3,11,0,99,30,40,50"),
+            &original.program
+        )
+        .starts_with(
+            "0..1: [1] -> [3500]\n  -     0: add [9], [10], [3]\n  +     0: .data 3500\n"
+        ));
+    }
+
+    #[test]
+    fn watch_writes() {
+        let state = run_day02(11, 10);
+        assert_eq!(
+            state.watch_log,
+            [
+                WatchEvent {
+                    pc: 0,
+                    address: 3,
+                    value: 90
+                },
+                WatchEvent {
+                    pc: 4,
+                    address: 0,
+                    value: 4500
+                },
+            ]
+        );
+    }
+}
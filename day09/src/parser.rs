@@ -0,0 +1,318 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    InvalidNumber,
+    MissingValue,
+    ExpectedComma,
+}
+
+/// Position and text of the token a program failed to parse at; lines and columns start at 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            ParseErrorKind::InvalidNumber => "invalid number",
+            ParseErrorKind::MissingValue => "missing value before",
+            ParseErrorKind::ExpectedComma => "expected ',' before",
+        };
+        write!(
+            f,
+            "line {}, column {}: {} `{}`",
+            self.line, self.column, what, self.token
+        )
+    }
+}
+
+struct Token {
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    fn parse(self) -> Result<i64, ParseError> {
+        self.text.parse::<i64>().map_err(|_| ParseError {
+            kind: ParseErrorKind::InvalidNumber,
+            line: self.line,
+            column: self.column,
+            token: self.text,
+        })
+    }
+}
+
+/// Parses comma separated Intcode. Whitespace (including newlines) may appear around values
+/// and `#` starts a comment that runs until the end of the line.
+pub fn parse_intcode(text: &str) -> Result<Vec<i64>, ParseError> {
+    let mut values = Vec::new();
+    let mut token: Option<Token> = None;
+    let mut token_ended = false;
+    let mut last_comma = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let code = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => line,
+        };
+        for (column_idx, (byte_idx, c)) in code.char_indices().enumerate() {
+            let (line, column) = (line_idx + 1, column_idx + 1);
+            if c == ',' {
+                match token.take() {
+                    Some(t) => values.push(t.parse()?),
+                    None => {
+                        return Err(ParseError {
+                            kind: ParseErrorKind::MissingValue,
+                            line,
+                            column,
+                            token: ",".to_string(),
+                        })
+                    }
+                }
+                token_ended = false;
+                last_comma = Some((line, column));
+            } else if c.is_whitespace() {
+                token_ended = token.is_some();
+            } else if token_ended {
+                return Err(ParseError {
+                    kind: ParseErrorKind::ExpectedComma,
+                    line,
+                    column,
+                    token: code[byte_idx..]
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .next()
+                        .unwrap()
+                        .to_string(),
+                });
+            } else {
+                token
+                    .get_or_insert(Token {
+                        text: String::new(),
+                        line,
+                        column,
+                    })
+                    .text
+                    .push(c);
+            }
+        }
+        token_ended = token.is_some();
+    }
+
+    match (token, last_comma) {
+        (Some(t), _) => values.push(t.parse()?),
+        (None, Some((line, column))) => {
+            return Err(ParseError {
+                kind: ParseErrorKind::MissingValue,
+                line,
+                column: column + 1,
+                token: "end of input".to_string(),
+            })
+        }
+        (None, None) => {}
+    }
+    Ok(values)
+}
+
+#[allow(dead_code)]
+pub fn write_intcode(values: &[i64]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FormatError {
+    BadMagic,
+    Truncated { offset: usize },
+    Overflow { offset: usize },
+    InvalidHexDigit { line: usize, column: usize },
+    OddHexLength,
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatError::BadMagic => write!(f, "not a binary Intcode program"),
+            FormatError::Truncated { offset } => write!(f, "value at byte {} is truncated", offset),
+            FormatError::Overflow { offset } => {
+                write!(f, "value at byte {} does not fit into 64 bit", offset)
+            }
+            FormatError::InvalidHexDigit { line, column } => {
+                write!(f, "line {}, column {}: invalid hex digit", line, column)
+            }
+            FormatError::OddHexLength => write!(f, "hex data has an odd number of digits"),
+        }
+    }
+}
+
+const BINARY_MAGIC: &[u8] = b"ICB1";
+const HEX_LINE_LENGTH: usize = 64;
+
+/// Binary format: a magic header followed by zigzag LEB128 varints, so small values of either sign take one byte.
+#[allow(dead_code)]
+pub fn encode_binary(values: &[i64]) -> Vec<u8> {
+    let mut bytes = BINARY_MAGIC.to_vec();
+    for value in values {
+        let mut zigzag = ((value << 1) ^ (value >> 63)) as u64;
+        loop {
+            let byte = (zigzag & 0x7f) as u8;
+            zigzag >>= 7;
+            if zigzag == 0 {
+                bytes.push(byte);
+                break;
+            }
+            bytes.push(byte | 0x80);
+        }
+    }
+    bytes
+}
+
+pub fn decode_binary(bytes: &[u8]) -> Result<Vec<i64>, FormatError> {
+    if !bytes.starts_with(BINARY_MAGIC) {
+        return Err(FormatError::BadMagic);
+    }
+    let mut values = Vec::new();
+    let mut offset = BINARY_MAGIC.len();
+    while offset < bytes.len() {
+        let start = offset;
+        let mut zigzag = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = match bytes.get(offset) {
+                Some(byte) => *byte,
+                None => return Err(FormatError::Truncated { offset: start }),
+            };
+            offset += 1;
+            if shift > 63 || (shift == 63 && byte & 0x7e != 0) {
+                return Err(FormatError::Overflow { offset: start });
+            }
+            zigzag |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        values.push((zigzag >> 1) as i64 ^ -((zigzag & 1) as i64));
+    }
+    Ok(values)
+}
+
+/// Hex format: the binary format as hex digits, wrapped into lines so it can be stored as text.
+#[allow(dead_code)]
+pub fn encode_hex(values: &[i64]) -> String {
+    let digits: String = encode_binary(values)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    let mut text = String::new();
+    for line in digits.as_bytes().chunks(HEX_LINE_LENGTH) {
+        text.push_str(std::str::from_utf8(line).unwrap());
+        text.push('\n');
+    }
+    text
+}
+
+pub fn decode_hex(text: &str) -> Result<Vec<i64>, FormatError> {
+    let mut digits = Vec::new();
+    for (line_idx, line) in text.lines().enumerate() {
+        for (column_idx, c) in line.chars().enumerate() {
+            if c.is_whitespace() {
+                continue;
+            }
+            match c.to_digit(16) {
+                Some(digit) => digits.push(digit as u8),
+                None => {
+                    return Err(FormatError::InvalidHexDigit {
+                        line: line_idx + 1,
+                        column: column_idx + 1,
+                    })
+                }
+            }
+        }
+    }
+    if digits.len() % 2 != 0 {
+        return Err(FormatError::OddHexLength);
+    }
+    let bytes: Vec<u8> = digits.chunks(2).map(|d| d[0] << 4 | d[1]).collect();
+    decode_binary(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_and_comments() {
+        assert_eq!(parse_intcode("1,0,0,0,99\n"), Ok(vec![1, 0, 0, 0, 99]));
+        assert_eq!(
+            parse_intcode("# header\n1101, 1,2 ,\n  0 # a,b\n,\t99\r\n"),
+            Ok(vec![1101, 1, 2, 0, 99])
+        );
+        assert_eq!(parse_intcode(" \n"), Ok(vec![]));
+    }
+
+    #[test]
+    fn diagnostics() {
+        assert_eq!(
+            parse_intcode("1,2,\n3,x4,5"),
+            Err(ParseError {
+                kind: ParseErrorKind::InvalidNumber,
+                line: 2,
+                column: 3,
+                token: "x4".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_intcode("1,,2").unwrap_err().to_string(),
+            "line 1, column 3: missing value before `,`"
+        );
+        assert_eq!(
+            parse_intcode("1,2 3,4").unwrap_err().to_string(),
+            "line 1, column 5: expected ',' before `3`"
+        );
+        assert_eq!(
+            parse_intcode("1,2,\n").unwrap_err().kind,
+            ParseErrorKind::MissingValue
+        );
+        assert_eq!(
+            parse_intcode("99999999999999999999").unwrap_err().kind,
+            ParseErrorKind::InvalidNumber
+        );
+    }
+
+    #[test]
+    fn binary_and_hex_roundtrip() {
+        let values = vec![0, 1, -1, 63, -64, 64, 1125899906842624, i64::MIN, i64::MAX];
+        let binary = encode_binary(&values);
+        assert_eq!(binary[4..8], [0, 2, 1, 126]);
+        assert_eq!(decode_binary(&binary), Ok(values.clone()));
+        assert_eq!(decode_hex(&encode_hex(&values)), Ok(values.clone()));
+        assert_eq!(parse_intcode(&write_intcode(&values)), Ok(values));
+    }
+
+    #[test]
+    fn binary_and_hex_errors() {
+        assert_eq!(decode_binary(b"1,2,3"), Err(FormatError::BadMagic));
+        assert_eq!(
+            decode_binary(b"ICB1\x02\x80"),
+            Err(FormatError::Truncated { offset: 5 })
+        );
+        assert_eq!(
+            decode_binary(b"ICB1\xff\xff\xff\xff\xff\xff\xff\xff\xff\x7f"),
+            Err(FormatError::Overflow { offset: 4 })
+        );
+        assert_eq!(
+            decode_hex("49434231\n0g"),
+            Err(FormatError::InvalidHexDigit { line: 2, column: 2 })
+        );
+        assert_eq!(decode_hex("4943423"), Err(FormatError::OddHexLength));
+    }
+}
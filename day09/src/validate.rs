@@ -0,0 +1,198 @@
+use crate::intcode::ProgramState;
+use crate::isa::*;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum LoadError {
+    UnknownOpcode {
+        address: u64,
+        opcode: i64,
+    },
+    UnsupportedMode {
+        address: u64,
+        operand: usize,
+        digit: i64,
+    },
+    ImmediateWrite {
+        address: u64,
+        operand: usize,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::UnknownOpcode { address, opcode } => {
+                write!(f, "address {}: unknown opcode {}", address, opcode)
+            }
+            LoadError::UnsupportedMode {
+                address,
+                operand,
+                digit,
+            } => write!(
+                f,
+                "address {}: parameter mode {} of operand {} not supported",
+                address, digit, operand
+            ),
+            LoadError::ImmediateWrite { address, operand } => write!(
+                f,
+                "address {}: write operand {} in immediate mode",
+                address, operand
+            ),
+        }
+    }
+}
+
+/// Checks the code reachable from address 0 against `isa`, so a program the dialect can't run
+/// fails before it starts instead of partway through.
+///
+/// Control flow follows fall-through and jumps to immediate targets; a conditional jump on
+/// an immediate condition only takes the direction it always goes. Computed jump targets and
+/// code written at run time aren't checked.
+pub fn validate(isa: &InstructionSet, program: &HashMap<u64, i64>) -> Result<(), LoadError> {
+    let get = |i| *program.get(&i).unwrap_or(&0);
+    let mut visited = BTreeSet::new();
+    let mut pending = vec![0];
+    while let Some(address) = pending.pop() {
+        if !visited.insert(address) {
+            continue;
+        }
+        let word = get(address);
+        let instruction = isa.get(word % 100).ok_or(LoadError::UnknownOpcode {
+            address,
+            opcode: word % 100,
+        })?;
+        let mut modes = Vec::new();
+        for operand in 0..instruction.arity {
+            let digit = word / (100 * 10i64.pow(operand as u32)) % 10;
+            let mode = match ParamMode::from_digit(digit) {
+                Some(mode) if isa.allows_mode(mode) => mode,
+                _ => {
+                    return Err(LoadError::UnsupportedMode {
+                        address,
+                        operand,
+                        digit,
+                    })
+                }
+            };
+            if instruction.is_write(operand) && mode == ParamMode::Immediate {
+                return Err(LoadError::ImmediateWrite { address, operand });
+            }
+            modes.push(mode);
+        }
+
+        let next = address + 1 + instruction.arity as u64;
+        if instruction.halts {
+            continue;
+        }
+        if !instruction.conditional {
+            pending.push(next);
+            continue;
+        }
+        let target = instruction.arity - 1;
+        let operands: Vec<i64> = (0..instruction.arity)
+            .map(|p| get(address + 1 + p as u64))
+            .collect();
+        // The direction only depends on the operands before the target, e.g. `jz 0, [rb+1]`
+        // always jumps wherever it goes.
+        let taken = if modes[..target]
+            .iter()
+            .all(|mode| *mode == ParamMode::Immediate)
+        {
+            let mut scratch = ProgramState::new(HashMap::new());
+            Some(matches!(
+                (instruction.exec)(&mut scratch, &operands),
+                Step::Jump(_)
+            ))
+        } else {
+            None
+        };
+        if taken != Some(true) {
+            pending.push(next);
+        }
+        if taken != Some(false) && modes[target] == ParamMode::Immediate {
+            pending.push(operands[target] as u64);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::parse_program;
+
+    #[test]
+    fn reachable_code() {
+        let day02 = InstructionSet::day02();
+        // Data after the halt isn't code.
+        assert_eq!(
+            validate(&day02, &parse_program("1,9,10,3,2,3,11,0,99,30,40,50")),
+            Ok(())
+        );
+        assert_eq!(
+            validate(&day02, &parse_program("1,0,0,0,3,0,99")),
+            Err(LoadError::UnknownOpcode {
+                address: 4,
+                opcode: 3
+            })
+        );
+        assert_eq!(
+            validate(&day02, &parse_program("1101,1,1,0,99")),
+            Err(LoadError::UnsupportedMode {
+                address: 0,
+                operand: 0,
+                digit: 1
+            })
+        );
+        assert_eq!(
+            validate(
+                &InstructionSet::day09(),
+                &parse_program("1,0,0,0,11101,1,1,0,99")
+            ),
+            Err(LoadError::ImmediateWrite {
+                address: 4,
+                operand: 2
+            })
+        );
+        assert!(validate(
+            &InstructionSet::day09(),
+            &parse_program(include_str!("input.txt"))
+        )
+        .is_ok());
+        assert!(validate(
+            &InstructionSet::day05(),
+            &parse_program(include_str!("input.txt"))
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn jump_targets() {
+        let day05 = InstructionSet::day05();
+        // Both directions of a jump on memory, the target of 1105 but not what follows it.
+        assert_eq!(
+            validate(&day05, &parse_program("1005,0,7,1105,1,9,42,42,42,99")),
+            Err(LoadError::UnknownOpcode {
+                address: 7,
+                opcode: 42
+            })
+        );
+        assert_eq!(validate(&day05, &parse_program("1105,1,4,42,99")), Ok(()));
+        assert_eq!(validate(&day05, &parse_program("1106,1,3,99")), Ok(()));
+        // Returns always jump, whatever the relative base holds.
+        assert_eq!(
+            validate(&InstructionSet::day09(), &parse_program("2106,0,0,42")),
+            Ok(())
+        );
+        assert_eq!(
+            validate(&day05, &parse_program("1106,0,4,99,42")),
+            Err(LoadError::UnknownOpcode {
+                address: 4,
+                opcode: 42
+            })
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use crate::field::AsteroidField;
+use std::f64::consts::PI;
+
+/// Added to every angular radius, so that point asteroids on the same ray still occlude
+/// each other despite rounding.
+const ANGLE_EPSILON: f64 = 1e-9;
+
+/// Round asteroid at a real-valued position, y grows downwards as on the map.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Body {
+    pub x: f64,
+    pub y: f64,
+    pub radius: f64,
+}
+
+impl Body {
+    fn distance(&self, other: &Body) -> f64 {
+        (other.x - self.x).hypot(other.y - self.y)
+    }
+
+    /// Clockwise angle of `other` as seen from here, 0 is up, in `[0, 2π)`.
+    fn angle_to(&self, other: &Body) -> f64 {
+        let angle = (other.x - self.x).atan2(self.y - other.y);
+        if angle < 0.0 {
+            angle + 2.0 * PI
+        } else {
+            angle
+        }
+    }
+
+    /// Half the angle `other` covers as seen from here.
+    fn angular_radius(&self, other: &Body) -> f64 {
+        (other.radius / self.distance(other)).min(1.0).asin() + ANGLE_EPSILON
+    }
+}
+
+/// Every asteroid of the map as a body with the given radius, in reading order.
+pub fn from_field(field: &AsteroidField, radius: f64) -> Vec<Body> {
+    field
+        .positions()
+        .into_iter()
+        .map(|p| Body {
+            x: p.x as f64,
+            y: p.y as f64,
+            radius,
+        })
+        .collect()
+}
+
+/// Union of angular intervals within `[0, 2π)`, kept sorted and disjoint.
+#[derive(Default)]
+struct Shadow {
+    intervals: Vec<(f64, f64)>,
+}
+
+impl Shadow {
+    /// Splits an interval around `center` at the 0/2π seam.
+    fn parts(center: f64, half_width: f64) -> Vec<(f64, f64)> {
+        if half_width >= PI {
+            return vec![(0.0, 2.0 * PI)];
+        }
+        let (start, end) = (center - half_width, center + half_width);
+        if start < 0.0 {
+            vec![(0.0, end), (start + 2.0 * PI, 2.0 * PI)]
+        } else if end > 2.0 * PI {
+            vec![(0.0, end - 2.0 * PI), (start, 2.0 * PI)]
+        } else {
+            vec![(start, end)]
+        }
+    }
+
+    fn covers(&self, center: f64, half_width: f64) -> bool {
+        Shadow::parts(center, half_width)
+            .iter()
+            .all(|(start, end)| {
+                self.intervals
+                    .iter()
+                    .any(|(s, e)| *s <= *start && *end <= *e)
+            })
+    }
+
+    fn add(&mut self, center: f64, half_width: f64) {
+        for (start, end) in Shadow::parts(center, half_width) {
+            self.intervals.push((start, end));
+        }
+        self.intervals
+            .sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let mut merged: Vec<(f64, f64)> = Vec::with_capacity(self.intervals.len());
+        for (start, end) in self.intervals.drain(..) {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        self.intervals = merged;
+    }
+}
+
+/// Indices of the bodies `station` can see: a body is hidden if the bodies closer to the
+/// station cover its whole angular interval. Bodies at the same distance don't hide each other.
+pub fn visible(bodies: &[Body], station: usize, alive: &[bool]) -> Vec<usize> {
+    let s = &bodies[station];
+    let mut order: Vec<usize> = (0..bodies.len())
+        .filter(|i| *i != station && alive[*i])
+        .collect();
+    order.sort_by(|a, b| {
+        s.distance(&bodies[*a])
+            .partial_cmp(&s.distance(&bodies[*b]))
+            .unwrap()
+    });
+
+    let mut shadow = Shadow::default();
+    let mut visible = Vec::new();
+    let mut i = 0;
+    while i < order.len() {
+        let distance = s.distance(&bodies[order[i]]);
+        let same_distance = order[i..]
+            .iter()
+            .take_while(|b| s.distance(&bodies[**b]) == distance)
+            .count();
+        let group = &order[i..(i + same_distance)];
+        for b in group {
+            if !shadow.covers(s.angle_to(&bodies[*b]), s.angular_radius(&bodies[*b])) {
+                visible.push(*b);
+            }
+        }
+        for b in group {
+            shadow.add(s.angle_to(&bodies[*b]), s.angular_radius(&bodies[*b]));
+        }
+        i += same_distance;
+    }
+    visible
+}
+
+pub fn count_visible(bodies: &[Body], station: usize) -> usize {
+    visible(bodies, station, &vec![true; bodies.len()]).len()
+}
+
+/// Same as the grid version: the body that sees the most others and how many,
+/// first one on ties.
+pub fn get_highest_visibility(bodies: &[Body]) -> Option<(usize, usize)> {
+    let mut best: Option<(usize, usize)> = None;
+    for station in 0..bodies.len() {
+        let count = count_visible(bodies, station);
+        if best.is_none_or(|(max, _)| count > max) {
+            best = Some((count, station));
+        }
+    }
+    best
+}
+
+/// Indices in the order the laser at `station` vaporizes them. Each rotation hits every
+/// body visible at its start clockwise from up, then visibility is recomputed.
+pub fn vaporization_order(bodies: &[Body], station: usize) -> Vec<usize> {
+    let s = &bodies[station];
+    let mut alive = vec![true; bodies.len()];
+    let mut order = Vec::new();
+    loop {
+        let mut rotation = visible(bodies, station, &alive);
+        if rotation.is_empty() {
+            return order;
+        }
+        rotation.sort_by(|a, b| {
+            s.angle_to(&bodies[*a])
+                .partial_cmp(&s.angle_to(&bodies[*b]))
+                .unwrap()
+        });
+        for b in rotation {
+            alive[b] = false;
+            order.push(b);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::visibility::Point;
+
+    const SAMPLE_33: &str = "......#.#.
+#..#.#....
+..#######.
+.#.#.###..
+.#..#.....
+..#....#.#
+#..#....#.
+.##.#..###
+##...#..#.
+.#....####";
+
+    const SAMPLE_210: &str = ".#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##";
+
+    fn best(text: &str) -> (usize, Point) {
+        let bodies = from_field(&AsteroidField::parse(text).unwrap(), 0.0);
+        let (count, station) = get_highest_visibility(&bodies).unwrap();
+        let b = bodies[station];
+        (
+            count,
+            Point {
+                x: b.x as i32,
+                y: b.y as i32,
+            },
+        )
+    }
+
+    #[test]
+    fn point_asteroids_match_grid() {
+        assert_eq!(
+            best(".#..#\n.....\n#####\n....#\n...##"),
+            (8, Point { x: 3, y: 4 })
+        );
+        assert_eq!(best(SAMPLE_33), (33, Point { x: 5, y: 8 }));
+        assert_eq!(best(SAMPLE_210), (210, Point { x: 11, y: 13 }));
+    }
+
+    #[test]
+    fn point_asteroids_laser_order() {
+        let bodies = from_field(&AsteroidField::parse(SAMPLE_210).unwrap(), 0.0);
+        let station = bodies
+            .iter()
+            .position(|b| b.x == 11.0 && b.y == 13.0)
+            .unwrap();
+        let order = vaporization_order(&bodies, station);
+        assert_eq!(order.len(), 299);
+        let nth = |n: usize| {
+            let b = bodies[order[n - 1]];
+            (b.x as i32, b.y as i32)
+        };
+        assert_eq!(nth(1), (11, 12));
+        assert_eq!(nth(2), (12, 1));
+        assert_eq!(nth(200), (8, 2));
+        assert_eq!(nth(299), (11, 1));
+    }
+
+    #[test]
+    fn radii() {
+        let body = |x, y, radius| Body { x, y, radius };
+        // A big blocker in front hides the small body behind it, but not one next to it.
+        let bodies = [
+            body(0.0, 0.0, 0.0),
+            body(2.0, 0.0, 1.0),
+            body(4.0, 0.5, 0.1),
+            body(4.0, 3.0, 0.1),
+        ];
+        assert_eq!(visible(&bodies, 0, &[true; 4]), [1, 3]);
+
+        // A body wider than the one in front stays visible around it.
+        let bodies = [
+            body(0.0, 0.0, 0.0),
+            body(2.0, 0.0, 1.0),
+            body(4.0, 0.0, 3.0),
+        ];
+        assert_eq!(count_visible(&bodies, 0), 2);
+
+        // The shadow wraps around straight up.
+        let bodies = [
+            body(0.0, 0.0, 0.0),
+            body(0.0, -2.0, 1.0),
+            body(-0.3, -5.0, 0.1),
+            body(0.3, -5.0, 0.1),
+        ];
+        assert_eq!(count_visible(&bodies, 0), 1);
+        assert_eq!(vaporization_order(&bodies, 0), [1, 3, 2]);
+    }
+}
@@ -0,0 +1,250 @@
+use crate::laser;
+use crate::visibility::*;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum FieldError {
+    Empty,
+    /// Lines need to have the same length as the first one.
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidChar {
+        line: usize,
+        column: usize,
+        c: char,
+    },
+    MultipleStations {
+        first: Point,
+        second: Point,
+    },
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldError::Empty => write!(f, "empty map"),
+            FieldError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}: expected {} cells, found {}",
+                line, expected, found
+            ),
+            FieldError::InvalidChar { line, column, c } => write!(
+                f,
+                "line {}, column {}: expected `#`, `.` or `X`, found `{}`",
+                line, column, c
+            ),
+            FieldError::MultipleStations { first, second } => write!(
+                f,
+                "second station at {},{}, first one is at {},{}",
+                second.x, second.y, first.x, first.y
+            ),
+        }
+    }
+}
+
+/// Rectangular map of asteroids. `#` marks an asteroid, `.` empty space and `X`
+/// an asteroid with the monitoring station, as in the puzzle examples.
+pub struct AsteroidField {
+    pub width: i32,
+    pub height: i32,
+    pub asteroids: HashSet<Point>,
+    pub station: Option<Point>,
+}
+
+impl AsteroidField {
+    pub fn parse(text: &str) -> Result<AsteroidField, FieldError> {
+        let mut field = AsteroidField {
+            width: 0,
+            height: 0,
+            asteroids: HashSet::new(),
+            station: None,
+        };
+        for (y, line) in text.lines().enumerate() {
+            let length = line.chars().count();
+            if y == 0 {
+                field.width = length as i32;
+            } else if length != field.width as usize {
+                return Err(FieldError::Ragged {
+                    line: y + 1,
+                    expected: field.width as usize,
+                    found: length,
+                });
+            }
+            for (x, c) in line.chars().enumerate() {
+                let p = Point {
+                    x: x as i32,
+                    y: y as i32,
+                };
+                match c {
+                    '.' => continue,
+                    '#' => {}
+                    'X' => match field.station {
+                        Some(first) => {
+                            return Err(FieldError::MultipleStations { first, second: p })
+                        }
+                        None => field.station = Some(p),
+                    },
+                    _ => {
+                        return Err(FieldError::InvalidChar {
+                            line: y + 1,
+                            column: x + 1,
+                            c,
+                        })
+                    }
+                }
+                field.asteroids.insert(p);
+            }
+            field.height += 1;
+        }
+        if field.width == 0 {
+            return Err(FieldError::Empty);
+        }
+        Ok(field)
+    }
+
+    /// Asteroids in reading order.
+    pub fn positions(&self) -> Vec<Point> {
+        let mut positions: Vec<Point> = self.asteroids.iter().copied().collect();
+        positions.sort_by_key(|p| (p.y, p.x));
+        positions
+    }
+
+    pub fn visibility_counts(&self) -> HashMap<Point, usize> {
+        let positions = self.positions();
+        positions
+            .iter()
+            .map(|p| (*p, count_visible(&positions, *p)))
+            .collect()
+    }
+
+    /// When the laser at `station` hits each asteroid, starting at 1.
+    pub fn vaporization_indices(&self, station: Point) -> HashMap<Point, usize> {
+        laser::vaporization_order(&self.positions(), station)
+            .enumerate()
+            .map(|(i, p)| (p, i + 1))
+            .collect()
+    }
+
+    /// The map with every labeled asteroid replaced by its label. Cells are padded to the
+    /// widest label and separated by spaces once labels have more than one digit.
+    pub fn render_annotated(&self, labels: &HashMap<Point, usize>) -> String {
+        let cell_width = labels
+            .values()
+            .map(|label| label.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let mut text = String::new();
+        for y in 0..self.height {
+            let cells: Vec<String> = (0..self.width)
+                .map(|x| {
+                    let p = Point { x, y };
+                    let cell = match labels.get(&p) {
+                        Some(label) => label.to_string(),
+                        None if self.station == Some(p) => "X".to_string(),
+                        None if self.asteroids.contains(&p) => "#".to_string(),
+                        None => ".".to_string(),
+                    };
+                    format!("{:>width$}", cell, width = cell_width)
+                })
+                .collect();
+            text.push_str(&cells.join(if cell_width > 1 { " " } else { "" }));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing() {
+        let field = AsteroidField::parse("#.X\n.#.\n").unwrap();
+        assert_eq!((field.width, field.height), (3, 2));
+        assert_eq!(field.station, Some(Point { x: 2, y: 0 }));
+        assert_eq!(
+            field.positions(),
+            [
+                Point { x: 0, y: 0 },
+                Point { x: 2, y: 0 },
+                Point { x: 1, y: 1 }
+            ]
+        );
+
+        assert_eq!(
+            AsteroidField::parse("#..\n.#").err(),
+            Some(FieldError::Ragged {
+                line: 2,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            AsteroidField::parse("#.\n.o").err(),
+            Some(FieldError::InvalidChar {
+                line: 2,
+                column: 2,
+                c: 'o'
+            })
+        );
+        assert_eq!(
+            AsteroidField::parse("X.X").err(),
+            Some(FieldError::MultipleStations {
+                first: Point { x: 0, y: 0 },
+                second: Point { x: 2, y: 0 }
+            })
+        );
+        assert_eq!(AsteroidField::parse("").err(), Some(FieldError::Empty));
+    }
+
+    #[test]
+    fn annotated_counts() {
+        let field = AsteroidField::parse(".#..#\n.....\n#####\n....#\n...##").unwrap();
+        assert_eq!(
+            field.render_annotated(&field.visibility_counts()),
+            ".7..7\n.....\n67775\n....7\n...87\n"
+        );
+    }
+
+    #[test]
+    fn annotated_vaporization() {
+        let field = AsteroidField::parse(
+            ".#....#####...#..
+##...##.#####..##
+##...#...#.#####.
+..#.....X...###..
+..#.#.....#....##",
+        )
+        .unwrap();
+        let mut indices = field.vaporization_indices(field.station.unwrap());
+        indices.retain(|_, i| *i <= 9);
+        assert_eq!(
+            field.render_annotated(&indices),
+            ".#....###24...#..
+##...##.13#67..9#
+##...#...5.8####.
+..#.....X...###..
+..#.#.....#....##
+"
+        );
+        let indices = field.vaporization_indices(field.station.unwrap());
+        assert_eq!(
+            field.render_annotated(&indices),
+            " . 26  .  .  .  . 29 30 31  2  4  .  .  . 33  .  .
+23 24  .  .  . 27 28  .  1  3 32  6  7  .  .  9 34
+21 22  .  .  . 25  .  .  .  5  .  8 10 11 12 13  .
+ .  . 20  .  .  .  .  .  X  .  .  . 14 35 36  .  .
+ .  . 19  . 18  .  .  .  .  . 17  .  .  .  . 16 15
+"
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use crate::visibility::*;
+use std::cmp::Ordering;
+
+/// 0 for directions from straight up clockwise to just before straight down, 1 for the rest.
+fn half(d: Point) -> u8 {
+    if d.x > 0 || (d.x == 0 && d.y < 0) {
+        0
+    } else {
+        1
+    }
+}
+
+/// Orders directions by their clockwise angle from up (y grows downwards),
+/// using only integer arithmetic.
+pub fn compare_clockwise(a: Point, b: Point) -> Ordering {
+    half(a).cmp(&half(b)).then_with(|| {
+        // Within a half turn, b lies clockwise of a if the cross product is positive.
+        let cross = a.x as i64 * b.y as i64 - a.y as i64 * b.x as i64;
+        0.cmp(&cross)
+    })
+}
+
+/// Asteroids in the order the laser vaporizes them.
+pub struct Vaporization {
+    /// Asteroids per direction in clockwise order, each sorted farthest first.
+    rays: Vec<Vec<Point>>,
+    ray: usize,
+}
+
+impl Iterator for Vaporization {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.rays.is_empty() {
+            return None;
+        }
+        let ray = &mut self.rays[self.ray];
+        let hit = ray.pop();
+        if ray.is_empty() {
+            self.rays.remove(self.ray);
+        } else {
+            self.ray += 1;
+        }
+        if self.ray >= self.rays.len() {
+            self.ray = 0;
+        }
+        hit
+    }
+}
+
+/// The laser starts pointing up and rotates clockwise, hitting the nearest remaining
+/// asteroid of every direction it passes.
+pub fn vaporization_order(asteroids: &[Point], station: Point) -> Vaporization {
+    let distance = |p: &Point| (p.x - station.x).abs() + (p.y - station.y).abs();
+    let mut targets: Vec<(Point, Point)> = asteroids
+        .iter()
+        .filter(|a| **a != station)
+        .map(|a| (direction(station, *a), *a))
+        .collect();
+    targets.sort_by(|(da, a), (db, b)| {
+        compare_clockwise(*da, *db)
+            .then(da.cmp(db))
+            .then_with(|| distance(b).cmp(&distance(a)))
+    });
+
+    let mut rays: Vec<Vec<Point>> = Vec::new();
+    let mut last_direction = None;
+    for (d, asteroid) in targets {
+        if last_direction == Some(d) {
+            rays.last_mut().unwrap().push(asteroid);
+        } else {
+            rays.push(vec![asteroid]);
+            last_direction = Some(d);
+        }
+    }
+    Vaporization { rays, ray: 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::AsteroidField;
+
+    const LARGE_SAMPLE: &str = ".#..##.###...#######
+##.############..##.
+.#.######.########.#
+.###.#######.####.#.
+#####.##.#.##.###.##
+..#####..#.#########
+####################
+#.####....###.#.#.##
+##.#################
+#####.##.###..####..
+..######..##.#######
+####.##.####...##..#
+.#####..#.######.###
+##...#.##########...
+#.##########.#######
+.####.#.###.###.#.##
+....##.##.###..#####
+.#.#.###########.###
+#.#.#.#####.####.###
+###.##.####.##.#..##";
+
+    fn field(text: &str) -> Vec<Point> {
+        AsteroidField::parse(text).unwrap().positions()
+    }
+
+    #[test]
+    fn clockwise_order() {
+        let mut directions = vec![
+            Point { x: -1, y: -1 },
+            Point { x: -1, y: 0 },
+            Point { x: 0, y: 1 },
+            Point { x: 1, y: -2 },
+            Point { x: 0, y: -1 },
+            Point { x: 1, y: 0 },
+            Point { x: 1, y: 1 },
+            Point { x: -1, y: 1 },
+        ];
+        directions.sort_by(|a, b| compare_clockwise(*a, *b));
+        assert_eq!(
+            directions,
+            [
+                Point { x: 0, y: -1 },
+                Point { x: 1, y: -2 },
+                Point { x: 1, y: 0 },
+                Point { x: 1, y: 1 },
+                Point { x: 0, y: 1 },
+                Point { x: -1, y: 1 },
+                Point { x: -1, y: 0 },
+                Point { x: -1, y: -1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn small_sample() {
+        let asteroids = field(
+            ".#....#####...#..
+##...##.#####..##
+##...#...#.#####.
+..#.....#...###..
+..#.#.....#....##",
+        );
+        let order: Vec<Point> = vaporization_order(&asteroids, Point { x: 8, y: 3 }).collect();
+        assert_eq!(order.len(), asteroids.len() - 1);
+        assert_eq!(
+            order[..9],
+            [
+                Point { x: 8, y: 1 },
+                Point { x: 9, y: 0 },
+                Point { x: 9, y: 1 },
+                Point { x: 10, y: 0 },
+                Point { x: 9, y: 2 },
+                Point { x: 11, y: 1 },
+                Point { x: 12, y: 1 },
+                Point { x: 11, y: 2 },
+                Point { x: 15, y: 1 },
+            ]
+        );
+        assert_eq!(order[order.len() - 1], Point { x: 14, y: 3 });
+    }
+
+    #[test]
+    fn large_sample() {
+        let asteroids = field(LARGE_SAMPLE);
+        let station = Point { x: 11, y: 13 };
+        let order: Vec<Point> = vaporization_order(&asteroids, station).collect();
+        let nth = |n: usize| order[n - 1];
+        assert_eq!(nth(1), Point { x: 11, y: 12 });
+        assert_eq!(nth(2), Point { x: 12, y: 1 });
+        assert_eq!(nth(3), Point { x: 12, y: 2 });
+        assert_eq!(nth(10), Point { x: 12, y: 8 });
+        assert_eq!(nth(20), Point { x: 16, y: 0 });
+        assert_eq!(nth(50), Point { x: 16, y: 9 });
+        assert_eq!(nth(100), Point { x: 10, y: 16 });
+        assert_eq!(nth(199), Point { x: 9, y: 6 });
+        assert_eq!(nth(200), Point { x: 8, y: 2 });
+        assert_eq!(nth(201), Point { x: 10, y: 9 });
+        assert_eq!(nth(299), Point { x: 11, y: 1 });
+        assert_eq!(order.len(), 299);
+
+        // Stopping early on a predicate.
+        let first_left = vaporization_order(&asteroids, station).position(|p| p.x < station.x);
+        assert_eq!(
+            first_left.map(|i| order[i]),
+            order.iter().find(|p| p.x < 11).copied()
+        );
+    }
+}
@@ -1,153 +1,107 @@
-use std::f64;
-
-#[derive(Debug, Copy, Clone, PartialEq)]
-struct Point {
-    x: i32,
-    y: i32,
+mod continuous;
+mod field;
+mod laser;
+mod visibility;
+mod visualize;
+
+use field::AsteroidField;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use visibility::Point;
+
+fn get_highest_visibility(field: &AsteroidField) -> (u32, Point) {
+    match visibility::best_station(&field.positions()) {
+        Some((count, station)) => (count as u32, station),
+        None => (0, Point { x: 0, y: 0 }),
+    }
 }
 
-fn is_visible(field: &Vec<Vec<u8>>, from: Point, to: Point) -> bool {
-    if from == to || field[to.y as usize][to.x as usize] != '#' as u8 {
-        return false;
+/// Writes `scene` as SVG or PNG depending on the extension of `output`, or to the terminal for `-`.
+fn write_scene(scene: &visualize::Scene, output: &str) {
+    if output == "-" {
+        print!("{}", scene.to_ansi());
+        return;
     }
-
-    let dx = to.x - from.x;
-    let dy = to.y - from.y;
-
-    for s in (std::cmp::min(0, dx) + 1)..std::cmp::max(0, dx) {
-        if s * dy % dx == 0 {
-            let x = s + from.x;
-            let y = s * dy / dx + from.y;
-            if x > 0 && y > 0 && x < field[0].len() as i32 && y < field.len() as i32 {
-                if field[y as usize][x as usize] == '#' as u8 {
-                    return false;
-                }
-            }
-        }
-    }
-
-    if dx == 0 {
-        for y in (std::cmp::min(from.y, to.y) + 1)..std::cmp::max(from.y, to.y) {
-            if field[y as usize][from.x as usize] == '#' as u8 {
-                return false;
-            }
-        }
+    match Path::new(output).extension().and_then(|ext| ext.to_str()) {
+        Some("svg") => std::fs::write(output, scene.to_svg(16)).unwrap(),
+        Some("png") => scene
+            .write_png(BufWriter::new(File::create(output).unwrap()), 16)
+            .unwrap(),
+        _ => panic!("unknown image format {}, expected svg or png", output),
     }
-
-    true
 }
 
-fn count_visibilties(field: &Vec<Vec<u8>>, p: Point) -> u32 {
-    let mut count = 0;
-    for y in 0..field.len() {
-        for x in 0..field[y].len() {
-            if is_visible(
-                field,
-                p,
-                Point {
-                    x: x as i32,
-                    y: y as i32,
-                },
-            ) {
-                count += 1;
+fn main() {
+    let puzzle_input = include_str!("input.txt");
+    let field = AsteroidField::parse(puzzle_input).unwrap_or_else(|e| panic!("{}", e));
+    let p1 = get_highest_visibility(&field);
+    let station = field.station.unwrap_or(p1.1);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    match args[..] {
+        [] => {
+            println!("part 1 {}", p1.0);
+            if let Some(p) = laser::vaporization_order(&field.positions(), station).nth(199) {
+                println!("part 2 {}", p.x * 100 + p.y);
             }
         }
-    }
-    count
-}
-
-fn get_highest_visibility(field: &Vec<Vec<u8>>) -> (u32, Point) {
-    let mut max = 0;
-    let mut maxp = Point { x: 0, y: 0 };
-    for y in 0..field.len() {
-        for x in 0..field[y].len() {
-            if field[y][x] == '#' as u8 {
-                let v = count_visibilties(
-                    field,
-                    Point {
-                        x: x as i32,
-                        y: y as i32,
-                    },
-                );
-                if v > max {
-                    max = v;
-                    maxp = Point {
-                        x: x as i32,
-                        y: y as i32,
-                    };
-                }
+        ["--counts"] => print!("{}", field.render_annotated(&field.visibility_counts())),
+        ["--order"] => print!(
+            "{}",
+            field.render_annotated(&field.vaporization_indices(station))
+        ),
+        ["--los", x, y, output] => {
+            let station = Point {
+                x: x.parse().unwrap(),
+                y: y.parse().unwrap(),
+            };
+            if !field.asteroids.contains(&station) {
+                panic!("no asteroid at {},{}", station.x, station.y);
             }
+            write_scene(&visualize::line_of_sight(&field, station), output);
         }
-    }
-    (max, maxp)
-}
-
-fn part2_2(field: &mut Vec<Vec<u8>>, station: Point) -> u32 {
-    let mut last_laser_angle = f64::consts::FRAC_PI_2 + 0.001;
-
-    let mut count = 0;
-
-    loop {
-        let mut any = false;
-        let mut cand_angle = -99999.0;
-        let mut cand_point = Point{x:99999, y:99999};
-        let mut cand_distsq = 999999;
-        for y in 0..field.len() {
-            for x in 0..field[y].len() {
-                if field[y as usize][x as usize] == '#' as u8 {
-                    let dx = x as i32 - station.x;
-                    let dy = y as i32 - station.y;
-                    let distsq = dx * dx + dy * dy;
-                    let mut curangle = (-dy as f64).atan2(dx as f64);
-                    if curangle < 0.0 {
-                        curangle = f64::consts::PI * 2.0 + curangle;
-                    }
-                    if (curangle < last_laser_angle && curangle > cand_angle) || (curangle == cand_angle && cand_distsq > distsq) {
-                        any = true;
-                        cand_angle = curangle;
-                        cand_distsq = distsq;
-                        cand_point = Point{x:x as i32, y:y as i32};
-                    }
+        ["--heatmap", output] => write_scene(&visualize::heatmap(&field), output),
+        ["--continuous", radius] => {
+            let bodies = continuous::from_field(&field, radius.parse().unwrap());
+            if let Some((count, station)) = continuous::get_highest_visibility(&bodies) {
+                let s = bodies[station];
+                println!("best station {},{} sees {}", s.x, s.y, count);
+                if let Some(b) = continuous::vaporization_order(&bodies, station).get(199) {
+                    println!("200th vaporized {},{}", bodies[*b].x, bodies[*b].y);
                 }
             }
         }
-        if any == false {
-            last_laser_angle = f64::consts::PI * 2.0 + 0.000001;
-        } else {
-            last_laser_angle = cand_angle;
-            field[cand_point.y as usize][cand_point.x as usize] = '.' as u8;
-            count += 1;
-            
-            if count == 200 {
-                println!("part 2 {}", cand_point.x * 100 + cand_point.y);
-            }
-        }
+        _ => panic!(
+            "usage: day10 [--counts | --order | --los x y file.(svg|png)|- | --heatmap file.(svg|png)|- | --continuous radius]"
+        ),
     }
 }
 
-fn parse_field(field: &str) -> Vec<Vec<u8>> {
-    field.lines().map(|line| line.bytes().collect()).collect()
-}
-
 #[cfg(test)]
 mod tests {
     #[test]
     fn samples_day10_part1() {
         assert_eq!(
-            super::get_highest_visibility(&super::parse_field(
-                ".#..#
+            super::get_highest_visibility(
+                &super::AsteroidField::parse(
+                    ".#..#
 .....
 #####
 ....#
 ...##"
-            ))
+                )
+                .unwrap()
+            )
             .0,
             8
         );
 
         assert_eq!(
-            super::get_highest_visibility(&super::parse_field(
-                ".#..#..###
+            super::get_highest_visibility(
+                &super::AsteroidField::parse(
+                    ".#..#..###
 ####.###.#
 ....###.#.
 ..###.##.#
@@ -157,14 +111,17 @@ mod tests {
 #..#.#.###
 .##...##.#
 .....#.#.."
-            ))
+                )
+                .unwrap()
+            )
             .0,
             41
         );
 
         assert_eq!(
-            super::get_highest_visibility(&super::parse_field(
-                ".#..##.###...#######
+            super::get_highest_visibility(
+                &super::AsteroidField::parse(
+                    ".#..##.###...#######
 ##.############..##.
 .#.######.########.#
 .###.#######.####.#.
@@ -184,21 +141,19 @@ mod tests {
 .#.#.###########.###
 #.#.#.#####.####.###
 ###.##.####.##.#..##"
-            ))
+                )
+                .unwrap()
+            )
             .0,
             210
         );
     }
 
     #[test]
-    fn samples_day10_part2() {}
-}
-
-fn main() {
-    let puzzle_input = include_str!("input.txt");
-    let mut field = parse_field(puzzle_input);
-    let p1 = get_highest_visibility(&field);
-    println!("part 1 {}", p1.0);
-
-    part2_2(&mut field, p1.1);
+    fn samples_day10_part2() {
+        let field = super::AsteroidField::parse(include_str!("input.txt")).unwrap();
+        let (_, station) = super::get_highest_visibility(&field);
+        let p = super::laser::vaporization_order(&field.positions(), station).nth(199);
+        assert_eq!(p.map(|p| p.x * 100 + p.y), Some(512));
+    }
 }
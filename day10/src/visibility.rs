@@ -0,0 +1,104 @@
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+fn gcd(a: i32, b: i32) -> i32 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Offset from `from` to `to` divided by the gcd of its components, so all points
+/// on the same ray from `from` share the same direction.
+pub fn direction(from: Point, to: Point) -> Point {
+    let dx = to.x - from.x;
+    let dy = to.y - from.y;
+    let g = gcd(dx, dy).max(1);
+    Point {
+        x: dx / g,
+        y: dy / g,
+    }
+}
+
+/// Number of asteroids with a free line of sight from `station`,
+/// which is one per distinct direction.
+pub fn count_visible(asteroids: &[Point], station: Point) -> usize {
+    let mut directions: Vec<Point> = asteroids
+        .iter()
+        .filter(|a| **a != station)
+        .map(|a| direction(station, *a))
+        .collect();
+    directions.sort_unstable();
+    directions.dedup();
+    directions.len()
+}
+
+/// The asteroid that sees the most other asteroids, first in reading order on ties.
+pub fn best_station(asteroids: &[Point]) -> Option<(usize, Point)> {
+    let mut best: Option<(usize, Point)> = None;
+    for station in asteroids {
+        let count = count_visible(asteroids, *station);
+        if best.is_none_or(|(max, _)| count > max) {
+            best = Some((count, *station));
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::field::AsteroidField;
+
+    fn field(text: &str) -> Vec<Point> {
+        AsteroidField::parse(text).unwrap().positions()
+    }
+
+    #[test]
+    fn directions() {
+        let origin = Point { x: 0, y: 0 };
+        assert_eq!(
+            direction(origin, Point { x: 4, y: -6 }),
+            Point { x: 2, y: -3 }
+        );
+        assert_eq!(
+            direction(origin, Point { x: 0, y: -5 }),
+            Point { x: 0, y: -1 }
+        );
+        assert_eq!(
+            direction(origin, Point { x: -3, y: 0 }),
+            Point { x: -1, y: 0 }
+        );
+        assert_eq!(direction(origin, origin), origin);
+    }
+
+    #[test]
+    fn row_and_column_zero() {
+        let row = field("###");
+        assert_eq!(count_visible(&row, Point { x: 0, y: 0 }), 1);
+        assert_eq!(count_visible(&row, Point { x: 1, y: 0 }), 2);
+        assert_eq!(best_station(&row), Some((2, Point { x: 1, y: 0 })));
+
+        let column = field("#\n#\n#\n.\n#");
+        assert_eq!(count_visible(&column, Point { x: 0, y: 0 }), 1);
+        assert_eq!(count_visible(&column, Point { x: 0, y: 4 }), 1);
+        assert_eq!(best_station(&column), Some((2, Point { x: 0, y: 1 })));
+
+        // Blockers on row 0 and column 0 of a diagonal.
+        let corner = field("#.#.#\n.....\n#.#..\n.....\n#...#");
+        assert_eq!(count_visible(&corner, Point { x: 0, y: 0 }), 3);
+        assert_eq!(count_visible(&corner, Point { x: 4, y: 0 }), 4);
+    }
+
+    #[test]
+    fn samples() {
+        let sample = field(".#..#\n.....\n#####\n....#\n...##");
+        assert_eq!(best_station(&sample), Some((8, Point { x: 3, y: 4 })));
+        assert_eq!(count_visible(&sample, Point { x: 4, y: 2 }), 5);
+        assert_eq!(best_station(&[]), None);
+    }
+}
@@ -0,0 +1,344 @@
+use crate::field::AsteroidField;
+use crate::visibility::*;
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write;
+
+const BACKGROUND: [u8; 3] = [16, 16, 24];
+const STATION: [u8; 3] = [230, 60, 230];
+const VISIBLE: [u8; 3] = [40, 200, 60];
+const OCCLUDED: [u8; 3] = [220, 40, 40];
+const RAY: [u8; 3] = [110, 110, 130];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Sight {
+    Station,
+    Visible,
+    /// Hidden behind `blocker`, the nearest asteroid in the same direction.
+    Occluded {
+        blocker: Point,
+    },
+}
+
+/// Whether each asteroid can be seen from `station`.
+pub fn sight_lines(field: &AsteroidField, station: Point) -> HashMap<Point, Sight> {
+    let distance = |p: &Point| (p.x - station.x).abs() + (p.y - station.y).abs();
+    let mut nearest: HashMap<Point, Point> = HashMap::new();
+    for asteroid in field.asteroids.iter().filter(|a| **a != station) {
+        let entry = nearest
+            .entry(direction(station, *asteroid))
+            .or_insert(*asteroid);
+        if distance(asteroid) < distance(entry) {
+            *entry = *asteroid;
+        }
+    }
+    field
+        .asteroids
+        .iter()
+        .map(|asteroid| {
+            let sight = if *asteroid == station {
+                Sight::Station
+            } else {
+                let blocker = nearest[&direction(station, *asteroid)];
+                if blocker == *asteroid {
+                    Sight::Visible
+                } else {
+                    Sight::Occluded { blocker }
+                }
+            };
+            (*asteroid, sight)
+        })
+        .collect()
+}
+
+pub struct Cell {
+    pub position: Point,
+    pub color: [u8; 3],
+    pub text: char,
+}
+
+/// Line between the centers of two cells.
+pub struct Ray {
+    pub from: Point,
+    pub to: Point,
+}
+
+/// Colored cells and rays on top of the field, ready for any of the output formats.
+pub struct Scene {
+    pub width: i32,
+    pub height: i32,
+    pub cells: Vec<Cell>,
+    pub rays: Vec<Ray>,
+}
+
+/// Asteroids colored by whether `station` sees them, with a ray through every blocker
+/// to the asteroid it hides.
+pub fn line_of_sight(field: &AsteroidField, station: Point) -> Scene {
+    let mut scene = Scene {
+        width: field.width,
+        height: field.height,
+        cells: Vec::new(),
+        rays: Vec::new(),
+    };
+    let sights = sight_lines(field, station);
+    for asteroid in field.positions() {
+        let (color, text) = match sights[&asteroid] {
+            Sight::Station => (STATION, 'X'),
+            Sight::Visible => (VISIBLE, '#'),
+            Sight::Occluded { .. } => {
+                scene.rays.push(Ray {
+                    from: station,
+                    to: asteroid,
+                });
+                (OCCLUDED, '#')
+            }
+        };
+        scene.cells.push(Cell {
+            position: asteroid,
+            color,
+            text,
+        });
+    }
+    scene
+}
+
+/// Blue for the fewest visible asteroids up to red for the most.
+fn heat_color(t: f64) -> [u8; 3] {
+    let t = t.clamp(0.0, 1.0);
+    [
+        (255.0 * t) as u8,
+        (80.0 * (1.0 - (2.0 * t - 1.0).abs())) as u8,
+        (255.0 * (1.0 - t)) as u8,
+    ]
+}
+
+/// Every asteroid colored by how many others it sees, the best station marked with `X`.
+pub fn heatmap(field: &AsteroidField) -> Scene {
+    let counts = field.visibility_counts();
+    let min = counts.values().copied().min().unwrap_or(0);
+    let max = counts.values().copied().max().unwrap_or(0);
+    let best = best_station(&field.positions()).map(|(_, p)| p);
+    let cells = field
+        .positions()
+        .into_iter()
+        .map(|p| Cell {
+            position: p,
+            color: heat_color((counts[&p] - min) as f64 / (max - min).max(1) as f64),
+            text: if Some(p) == best { 'X' } else { '#' },
+        })
+        .collect();
+    Scene {
+        width: field.width,
+        height: field.height,
+        cells,
+        rays: Vec::new(),
+    }
+}
+
+fn hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+impl Scene {
+    pub fn to_svg(&self, scale: i32) -> String {
+        let center = |v: i32| v * scale + scale / 2;
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = self.width * scale,
+            h = self.height * scale
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+            hex(BACKGROUND)
+        )
+        .unwrap();
+        for ray in self.rays.iter() {
+            writeln!(
+                svg,
+                "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-dasharray=\"2\"/>",
+                center(ray.from.x),
+                center(ray.from.y),
+                center(ray.to.x),
+                center(ray.to.y),
+                hex(RAY)
+            )
+            .unwrap();
+        }
+        for cell in self.cells.iter() {
+            writeln!(
+                svg,
+                "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"{}\"/>",
+                center(cell.position.x),
+                center(cell.position.y),
+                scale * 2 / 5,
+                hex(cell.color)
+            )
+            .unwrap();
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// RGB pixels, each cell a `scale`×`scale` square, rays drawn below the cells.
+    pub fn rasterize(&self, scale: i32) -> Vec<u8> {
+        let width = self.width * scale;
+        let mut pixels: Vec<u8> = BACKGROUND
+            .iter()
+            .copied()
+            .cycle()
+            .take((width * self.height * scale * 3) as usize)
+            .collect();
+        let mut set = |x: i32, y: i32, color: [u8; 3]| {
+            let i = ((x + y * width) * 3) as usize;
+            pixels[i..(i + 3)].copy_from_slice(&color);
+        };
+        for ray in self.rays.iter() {
+            let (x0, y0) = (
+                ray.from.x * scale + scale / 2,
+                ray.from.y * scale + scale / 2,
+            );
+            let (x1, y1) = (ray.to.x * scale + scale / 2, ray.to.y * scale + scale / 2);
+            let steps = (x1 - x0).abs().max((y1 - y0).abs()).max(1);
+            for s in 0..=steps {
+                set(x0 + (x1 - x0) * s / steps, y0 + (y1 - y0) * s / steps, RAY);
+            }
+        }
+        let margin = scale / 5;
+        for cell in self.cells.iter() {
+            for y in margin..(scale - margin) {
+                for x in margin..(scale - margin) {
+                    set(
+                        cell.position.x * scale + x,
+                        cell.position.y * scale + y,
+                        cell.color,
+                    );
+                }
+            }
+        }
+        pixels
+    }
+
+    pub fn write_png(&self, out: impl Write, scale: i32) -> Result<(), png::EncodingError> {
+        let mut encoder = png::Encoder::new(
+            out,
+            (self.width * scale) as u32,
+            (self.height * scale) as u32,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()?
+            .write_image_data(&self.rasterize(scale))
+    }
+
+    /// Colored cells for true color terminals. Empty grid points that a ray passes through
+    /// exactly are shown as `·`.
+    pub fn to_ansi(&self) -> String {
+        let cells: HashMap<Point, &Cell> = self.cells.iter().map(|c| (c.position, c)).collect();
+        let mut on_ray = std::collections::HashSet::new();
+        for ray in self.rays.iter() {
+            let step = direction(ray.from, ray.to);
+            let mut p = ray.from;
+            while p != ray.to {
+                on_ray.insert(p);
+                p = Point {
+                    x: p.x + step.x,
+                    y: p.y + step.y,
+                };
+            }
+        }
+
+        let mut text = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let p = Point { x, y };
+                match cells.get(&p) {
+                    Some(cell) => {
+                        let [r, g, b] = cell.color;
+                        write!(text, "\x1b[38;2;{};{};{}m{}\x1b[0m", r, g, b, cell.text).unwrap();
+                    }
+                    None if on_ray.contains(&p) => text.push('·'),
+                    None => text.push('.'),
+                }
+            }
+            text.push('\n');
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> AsteroidField {
+        AsteroidField::parse(".#..#\n.....\n#####\n....#\n...##").unwrap()
+    }
+
+    #[test]
+    fn sights() {
+        let field = sample();
+        let station = Point { x: 3, y: 4 };
+        let sights = sight_lines(&field, station);
+        assert_eq!(sights[&station], Sight::Station);
+        assert_eq!(
+            sights[&Point { x: 1, y: 0 }],
+            Sight::Occluded {
+                blocker: Point { x: 2, y: 2 }
+            }
+        );
+        assert_eq!(sights[&Point { x: 4, y: 4 }], Sight::Visible);
+        let visible = sights.values().filter(|s| **s == Sight::Visible).count();
+        assert_eq!(visible, count_visible(&field.positions(), station));
+    }
+
+    #[test]
+    fn scenes() {
+        let field = sample();
+        let scene = line_of_sight(&field, Point { x: 3, y: 4 });
+        assert_eq!(scene.cells.len(), 10);
+        assert_eq!(scene.rays.len(), 1);
+
+        let svg = scene.to_svg(10);
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"50\" height=\"50\"")
+        );
+        assert_eq!(svg.matches("<circle").count(), 10);
+        assert!(svg.contains("<line x1=\"35\" y1=\"45\" x2=\"15\" y2=\"5\""));
+
+        let ansi = scene.to_ansi();
+        assert_eq!(ansi.lines().count(), 5);
+        assert!(ansi.lines().nth(1).unwrap().starts_with(".."));
+        assert!(ansi.contains("\x1b[38;2;230;60;230mX\x1b[0m"));
+
+        let mut png = Vec::new();
+        scene.write_png(&mut png, 4).unwrap();
+        let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (20, 20));
+        assert_eq!(pixels[..3], BACKGROUND);
+        // Center of the station cell.
+        let i = ((3 * 4 + 2) + (4 * 4 + 2) * 20) * 3;
+        assert_eq!(pixels[i..(i + 3)], STATION);
+    }
+
+    #[test]
+    fn heatmap_marks_best() {
+        let scene = heatmap(&sample());
+        let best = scene.cells.iter().find(|c| c.text == 'X').unwrap();
+        assert_eq!(best.position, Point { x: 3, y: 4 });
+        assert_eq!(best.color, heat_color(1.0));
+        // (4,2) sees the fewest asteroids.
+        let worst = scene
+            .cells
+            .iter()
+            .find(|c| c.position == Point { x: 4, y: 2 })
+            .unwrap();
+        assert_eq!(worst.color, heat_color(0.0));
+    }
+}
@@ -0,0 +1,347 @@
+use crate::hull::*;
+use crate::robot::*;
+use std::fs::File;
+use std::io;
+use std::io::{BufWriter, Write};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::time::Duration;
+
+// Palette indices of rendered frames.
+const UNPAINTED: u8 = 0;
+const BLACK: u8 = 1;
+const WHITE: u8 = 2;
+const ROBOT: u8 = 3;
+const ROBOT_FRONT: u8 = 4;
+
+const PALETTE: [[u8; 3]; 5] = [
+    [40, 40, 56],
+    [0, 0, 0],
+    [255, 255, 255],
+    [220, 40, 40],
+    [255, 220, 0],
+];
+
+/// Replays `history`, calling `f` with the hull and robot before the first step,
+/// after every `stride`th step and after the last step.
+pub fn replay(history: &History, stride: NonZeroUsize, mut f: impl FnMut(&Hull, Point, Heading)) {
+    let mut hull = history.initial_hull.clone();
+    for (i, step) in history.steps.iter().enumerate() {
+        if i % stride.get() == 0 {
+            f(&hull, step.position, step.heading);
+        }
+        hull.paint(step.position, step.paint);
+    }
+    f(&hull, history.position, history.heading);
+}
+
+/// Inclusive area covering every panel and every position the robot was at.
+pub fn bounds(history: &History) -> (Point, Point) {
+    let points = history
+        .initial_hull
+        .panels
+        .keys()
+        .chain(history.hull.panels.keys())
+        .chain(history.steps.iter().map(|step| &step.position))
+        .chain(std::iter::once(&history.position));
+    let mut min = history.position;
+    let mut max = history.position;
+    for p in points {
+        min = Point {
+            x: std::cmp::min(min.x, p.x),
+            y: std::cmp::min(min.y, p.y),
+        };
+        max = Point {
+            x: std::cmp::max(max.x, p.x),
+            y: std::cmp::max(max.y, p.y),
+        };
+    }
+    (min, max)
+}
+
+/// Frame as palette indices, each panel is `scale`×`scale` pixels.
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn render(
+        hull: &Hull,
+        position: Point,
+        heading: Heading,
+        (min, max): (Point, Point),
+        scale: usize,
+    ) -> Frame {
+        let width = (max.x - min.x + 1) as usize * scale;
+        let height = (max.y - min.y + 1) as usize * scale;
+        let mut frame = Frame {
+            width,
+            height,
+            pixels: vec![UNPAINTED; width * height],
+        };
+        for (p, color) in hull.panels.iter() {
+            frame.fill_panel(*p, min, scale, if *color == 1 { WHITE } else { BLACK });
+        }
+
+        frame.fill_panel(position, min, scale, ROBOT);
+        // Mark the edge of the robot's panel it is facing.
+        if scale > 1 {
+            let x0 = (position.x - min.x) as usize * scale;
+            let y0 = (position.y - min.y) as usize * scale;
+            for i in 1..(scale - 1).max(2) {
+                let (x, y) = match heading {
+                    Heading::Up => (x0 + i, y0),
+                    Heading::Right => (x0 + scale - 1, y0 + i),
+                    Heading::Down => (x0 + i, y0 + scale - 1),
+                    Heading::Left => (x0, y0 + i),
+                };
+                frame.pixels[x + y * width] = ROBOT_FRONT;
+            }
+        }
+        frame
+    }
+
+    fn fill_panel(&mut self, p: Point, min: Point, scale: usize, color: u8) {
+        let x0 = (p.x - min.x) as usize * scale;
+        let y0 = (p.y - min.y) as usize * scale;
+        for y in y0..(y0 + scale) {
+            for x in x0..(x0 + scale) {
+                self.pixels[x + y * self.width] = color;
+            }
+        }
+    }
+
+    pub fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        for index in self.pixels.iter() {
+            out.write_all(&PALETTE[*index as usize])?;
+        }
+        Ok(())
+    }
+
+    /// Smallest rectangle (x, y, width, height) containing all pixels that differ from `other`.
+    fn changed_rect(&self, other: &Frame) -> Option<(usize, usize, usize, usize)> {
+        let mut rect: Option<(usize, usize, usize, usize)> = None;
+        for (i, (a, b)) in self.pixels.iter().zip(other.pixels.iter()).enumerate() {
+            if a == b {
+                continue;
+            }
+            let (x, y) = (i % self.width, i / self.width);
+            rect = Some(match rect {
+                None => (x, y, x, y),
+                Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            });
+        }
+        rect.map(|(x0, y0, x1, y1)| (x0, y0, x1 - x0 + 1, y1 - y0 + 1))
+    }
+
+    fn crop(&self, (x0, y0, width, height): (usize, usize, usize, usize)) -> Vec<u8> {
+        (y0..(y0 + height))
+            .flat_map(|y| self.pixels[(x0 + y * self.width)..(x0 + width + y * self.width)].iter())
+            .copied()
+            .collect()
+    }
+}
+
+/// Writes an animated GIF; after the first frame only the changed area of each frame is stored.
+pub fn write_gif(
+    history: &History,
+    out: impl Write,
+    scale: usize,
+    stride: NonZeroUsize,
+    frame_delay: Duration,
+) -> Result<(), gif::EncodingError> {
+    let bounds = bounds(history);
+    let (min, max) = bounds;
+    let width = (max.x - min.x + 1) as usize * scale;
+    let height = (max.y - min.y + 1) as usize * scale;
+    let palette: Vec<u8> = PALETTE.iter().flatten().copied().collect();
+    let mut encoder = gif::Encoder::new(out, width as u16, height as u16, &palette)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    let delay = (frame_delay.as_millis() / 10) as u16;
+    let mut previous: Option<Frame> = None;
+    let mut result = Ok(());
+    replay(history, stride, |hull, position, heading| {
+        if result.is_err() {
+            return;
+        }
+        let frame = Frame::render(hull, position, heading, bounds, scale);
+        let rect = match &previous {
+            None => Some((0, 0, width, height)),
+            Some(previous) => frame.changed_rect(previous),
+        }
+        .unwrap_or((0, 0, 1, 1));
+        let mut gif_frame =
+            gif::Frame::from_indexed_pixels(rect.2 as u16, rect.3 as u16, frame.crop(rect), None);
+        gif_frame.left = rect.0 as u16;
+        gif_frame.top = rect.1 as u16;
+        gif_frame.delay = delay;
+        result = encoder.write_frame(&gif_frame);
+        previous = Some(frame);
+    });
+    result
+}
+
+/// Writes one PPM file per frame into `directory` and returns the number of frames.
+pub fn write_ppm_frames(
+    history: &History,
+    directory: &Path,
+    scale: usize,
+    stride: NonZeroUsize,
+) -> io::Result<usize> {
+    std::fs::create_dir_all(directory)?;
+    let bounds = bounds(history);
+    let mut count = 0;
+    let mut result = Ok(());
+    replay(history, stride, |hull, position, heading| {
+        if result.is_err() {
+            return;
+        }
+        let path = directory.join(format!("frame_{:05}.ppm", count));
+        result = File::create(path).and_then(|file| {
+            Frame::render(hull, position, heading, bounds, scale)
+                .write_ppm(&mut BufWriter::new(file))
+        });
+        count += 1;
+    });
+    result.map(|_| count)
+}
+
+/// Same characters as `print_panels`, with the robot drawn as a colored arrow.
+pub fn ansi_frame(
+    hull: &Hull,
+    position: Point,
+    heading: Heading,
+    (min, max): (Point, Point),
+) -> String {
+    let mut text = String::new();
+    for y in min.y..(max.y + 1) {
+        for x in min.x..(max.x + 1) {
+            let p = Point { x, y };
+            if p == position {
+                let arrow = match heading {
+                    Heading::Up => '^',
+                    Heading::Right => '>',
+                    Heading::Down => 'v',
+                    Heading::Left => '<',
+                };
+                text.push_str(&format!("\x1b[1;31m{}\x1b[0m", arrow));
+                continue;
+            }
+            text.push(match hull.panels.get(&p) {
+                Some(1) => '█',
+                Some(_) => '.',
+                None => ' ',
+            });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Plays the history as terminal animation, waiting `frame_delay` between frames.
+pub fn play_ansi(
+    history: &History,
+    out: &mut impl Write,
+    stride: NonZeroUsize,
+    frame_delay: Duration,
+) -> io::Result<()> {
+    let bounds = bounds(history);
+    write!(out, "\x1b[2J")?;
+    let mut result = Ok(());
+    replay(history, stride, |hull, position, heading| {
+        if result.is_ok() {
+            result = write!(out, "\x1b[H{}", ansi_frame(hull, position, heading, bounds))
+                .and_then(|_| out.flush());
+            std::thread::sleep(frame_delay);
+        }
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_history() -> History {
+        let mut controller = ScriptedController::new(&[
+            (1, Turn::Left),
+            (0, Turn::Left),
+            (1, Turn::Left),
+            (1, Turn::Left),
+            (0, Turn::Right),
+            (1, Turn::Left),
+            (1, Turn::Left),
+        ]);
+        run(&mut controller, Hull::default())
+    }
+
+    #[test]
+    fn replay_frames() {
+        let history = sample_history();
+        let mut positions = Vec::new();
+        replay(&history, NonZeroUsize::new(3).unwrap(), |_, position, _| {
+            positions.push(position)
+        });
+        assert_eq!(
+            positions,
+            [
+                Point { x: 0, y: 0 },
+                Point { x: 0, y: 1 },
+                Point { x: 1, y: -1 },
+                Point { x: 0, y: -1 },
+            ]
+        );
+        assert_eq!(
+            bounds(&history),
+            (Point { x: -1, y: -1 }, Point { x: 1, y: 1 })
+        );
+    }
+
+    #[test]
+    fn ansi_final_frame() {
+        let history = sample_history();
+        let frame = ansi_frame(
+            &history.hull,
+            history.position,
+            history.heading,
+            bounds(&history),
+        );
+        assert_eq!(frame, " \x1b[1;31m<\x1b[0m█\n..█\n██ \n");
+    }
+
+    #[test]
+    fn rendered_frames() {
+        let history = sample_history();
+        let frame = Frame::render(
+            &history.hull,
+            history.position,
+            history.heading,
+            bounds(&history),
+            3,
+        );
+        assert_eq!((frame.width, frame.height), (9, 9));
+        assert_eq!(frame.pixels[0], UNPAINTED);
+        assert_eq!(frame.pixels[3..6], [ROBOT, ROBOT, ROBOT]);
+        assert_eq!(frame.pixels[12..15], [ROBOT_FRONT, ROBOT, ROBOT]);
+
+        let mut ppm = Vec::new();
+        frame.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n9 9\n255\n"));
+        assert_eq!(ppm.len(), 11 + 9 * 9 * 3);
+
+        let mut gif = Vec::new();
+        write_gif(
+            &history,
+            &mut gif,
+            3,
+            NonZeroUsize::new(1).unwrap(),
+            Duration::from_millis(100),
+        )
+        .unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+    }
+}
@@ -0,0 +1,345 @@
+use crate::hull::*;
+use std::convert::TryFrom;
+use std::fmt;
+use std::fmt::Write as _;
+use std::io;
+use std::io::Write;
+
+/// Colors for painted panels by color value plus the background for unpainted panels.
+/// Color values without an entry in `colors` are drawn as `unknown`, so they don't pass
+/// for any color the palette has.
+#[derive(Clone)]
+pub struct Palette {
+    pub unpainted: [u8; 3],
+    pub colors: Vec<[u8; 3]>,
+    pub unknown: [u8; 3],
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette {
+            unpainted: [40, 40, 56],
+            colors: vec![
+                [0, 0, 0],
+                [255, 255, 255],
+                [220, 40, 40],
+                [40, 180, 60],
+                [40, 80, 220],
+                [240, 200, 0],
+                [200, 60, 200],
+                [0, 200, 200],
+            ],
+            unknown: [255, 0, 255],
+        }
+    }
+}
+
+impl Palette {
+    pub fn color(&self, panel: Option<i64>) -> [u8; 3] {
+        match panel {
+            Some(color) => usize::try_from(color)
+                .ok()
+                .and_then(|i| self.colors.get(i))
+                .copied()
+                .unwrap_or(self.unknown),
+            None => self.unpainted,
+        }
+    }
+}
+
+pub struct ImageOptions {
+    /// Pixels per panel side.
+    pub scale: usize,
+    pub palette: Palette,
+}
+
+impl Default for ImageOptions {
+    fn default() -> ImageOptions {
+        ImageOptions {
+            scale: 8,
+            palette: Palette::default(),
+        }
+    }
+}
+
+/// Area of all painted panels, a single panel at the origin for an empty hull.
+fn painted_bounds(hull: &Hull) -> (Point, Point) {
+    let origin = Point { x: 0, y: 0 };
+    hull.bounds().unwrap_or((origin, origin))
+}
+
+/// Returns width, height and RGB pixels of the painted area.
+pub fn rasterize(hull: &Hull, options: &ImageOptions) -> (usize, usize, Vec<u8>) {
+    let (min, max) = painted_bounds(hull);
+    let scale = options.scale;
+    let width = (max.x - min.x + 1) as usize * scale;
+    let height = (max.y - min.y + 1) as usize * scale;
+    let mut pixels = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        for x in 0..width {
+            let p = Point {
+                x: min.x + (x / scale) as i32,
+                y: min.y + (y / scale) as i32,
+            };
+            pixels.extend_from_slice(&options.palette.color(hull.panels.get(&p).copied()));
+        }
+    }
+    (width, height, pixels)
+}
+
+pub fn write_ppm(hull: &Hull, options: &ImageOptions, out: &mut impl Write) -> io::Result<()> {
+    let (width, height, pixels) = rasterize(hull, options);
+    write!(out, "P6\n{} {}\n255\n", width, height)?;
+    out.write_all(&pixels)
+}
+
+pub fn write_png(
+    hull: &Hull,
+    options: &ImageOptions,
+    out: impl Write,
+) -> Result<(), png::EncodingError> {
+    let (width, height, pixels) = rasterize(hull, options);
+    let mut encoder = png::Encoder::new(out, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels)
+}
+
+/// One `rect` per painted panel on top of a background in the unpainted color.
+pub fn to_svg(hull: &Hull, options: &ImageOptions) -> String {
+    let (min, max) = painted_bounds(hull);
+    let scale = options.scale as i32;
+    let width = (max.x - min.x + 1) * scale;
+    let height = (max.y - min.y + 1) * scale;
+    let hex = |[r, g, b]: [u8; 3]| format!("#{:02x}{:02x}{:02x}", r, g, b);
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" shape-rendering=\"crispEdges\">",
+        w = width,
+        h = height
+    )
+    .unwrap();
+    writeln!(
+        svg,
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+        width,
+        height,
+        hex(options.palette.unpainted)
+    )
+    .unwrap();
+    let mut panels: Vec<_> = hull.panels.iter().collect();
+    panels.sort_by_key(|(p, _)| (p.y, p.x));
+    for (p, color) in panels {
+        writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{s}\" height=\"{s}\" fill=\"{}\"/>",
+            (p.x - min.x) * scale,
+            (p.y - min.y) * scale,
+            hex(options.palette.color(Some(*color))),
+            s = scale
+        )
+        .unwrap();
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Character for a panel in the text format: `.` black, `#` white, then `2`-`9` and `a`-`z`.
+pub fn panel_char(color: i64) -> Option<char> {
+    match color {
+        0 => Some('.'),
+        1 => Some('#'),
+        2..=35 => std::char::from_digit(color as u32, 36),
+        _ => None,
+    }
+}
+
+#[allow(dead_code)]
+fn char_panel(c: char) -> Option<i64> {
+    match c {
+        '.' => Some(0),
+        '#' => Some(1),
+        '0' | '1' => None,
+        _ => c.to_digit(36).map(|d| d as i64),
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq)]
+pub enum TextError {
+    MissingOrigin,
+    InvalidChar { line: usize, column: usize, c: char },
+    NoCharForColor { point: Point, color: i64 },
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextError::MissingOrigin => write!(f, "expected `origin <x> <y>` as first line"),
+            TextError::InvalidChar { line, column, c } => {
+                write!(f, "line {}, column {}: invalid panel `{}`", line, column, c)
+            }
+            TextError::NoCharForColor { point, color } => write!(
+                f,
+                "panel {},{}: no character for color {}",
+                point.x, point.y, color
+            ),
+        }
+    }
+}
+
+/// Text format for storing expected hulls in tests: an `origin <x> <y>` line with the
+/// position of the first character, followed by one line per row. Spaces are unpainted.
+/// Fails for colors without a `panel_char`.
+pub fn to_text(hull: &Hull) -> Result<String, TextError> {
+    let (min, max) = painted_bounds(hull);
+    let mut text = format!("origin {} {}\n", min.x, min.y);
+    for y in min.y..(max.y + 1) {
+        let row: String = (min.x..(max.x + 1))
+            .map(|x| {
+                let point = Point { x, y };
+                match hull.panels.get(&point) {
+                    Some(color) => panel_char(*color).ok_or(TextError::NoCharForColor {
+                        point,
+                        color: *color,
+                    }),
+                    None => Ok(' '),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        text.push_str(row.trim_end());
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+#[allow(dead_code)]
+pub fn from_text(text: &str) -> Result<Hull, TextError> {
+    let mut lines = text.lines();
+    let origin: Vec<i32> = match lines.next().and_then(|l| l.strip_prefix("origin ")) {
+        Some(coords) => coords
+            .split_whitespace()
+            .map(|c| c.parse().map_err(|_| TextError::MissingOrigin))
+            .collect::<Result<_, _>>()?,
+        None => return Err(TextError::MissingOrigin),
+    };
+    if origin.len() != 2 {
+        return Err(TextError::MissingOrigin);
+    }
+
+    let mut hull = Hull::default();
+    for (row, line) in lines.enumerate() {
+        for (column, c) in line.chars().enumerate() {
+            if c == ' ' {
+                continue;
+            }
+            let color = char_panel(c).ok_or(TextError::InvalidChar {
+                line: row + 2,
+                column: column + 1,
+                c,
+            })?;
+            let p = Point {
+                x: origin[0] + column as i32,
+                y: origin[1] + row as i32,
+            };
+            hull.paint(p, color);
+        }
+    }
+    Ok(hull)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "origin -1 -1
+  #
+..#
+##2
+";
+
+    #[test]
+    fn text_roundtrip() {
+        let hull = from_text(SAMPLE).unwrap();
+        assert_eq!(hull.painted(), 7);
+        assert_eq!(hull.panels.get(&Point { x: 1, y: 1 }), Some(&2));
+        assert_eq!(hull.panels.get(&Point { x: -1, y: -1 }), None);
+        assert_eq!(to_text(&hull), Ok(SAMPLE.to_string()));
+        assert_eq!(
+            from_text("origin 0 0\n#x?"),
+            Err(TextError::InvalidChar {
+                line: 2,
+                column: 3,
+                c: '?'
+            })
+        );
+        assert_eq!(from_text("#.#"), Err(TextError::MissingOrigin));
+    }
+
+    #[test]
+    fn text_unsupported_colors() {
+        for color in [-1, 36, 40].iter() {
+            let mut hull = from_text(SAMPLE).unwrap();
+            hull.paint(Point { x: 0, y: 0 }, *color);
+            assert_eq!(
+                to_text(&hull),
+                Err(TextError::NoCharForColor {
+                    point: Point { x: 0, y: 0 },
+                    color: *color
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn raster_formats() {
+        let hull = from_text(SAMPLE).unwrap();
+        let options = ImageOptions {
+            scale: 2,
+            ..ImageOptions::default()
+        };
+
+        let mut ppm = Vec::new();
+        write_ppm(&hull, &options, &mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n6 6\n255\n"));
+        assert_eq!(ppm[11..14], options.palette.unpainted);
+        assert_eq!(ppm[ppm.len() - 3..], [220, 40, 40]);
+
+        let mut png = Vec::new();
+        write_png(&hull, &options, &mut png).unwrap();
+        let mut reader = png::Decoder::new(&png[..]).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        assert_eq!((info.width, info.height), (6, 6));
+        assert_eq!(pixels[..], ppm[11..]);
+    }
+
+    #[test]
+    fn palette_colors() {
+        let palette = Palette::default();
+        assert_eq!(palette.color(Some(2)), [220, 40, 40]);
+        assert_eq!(palette.color(None), palette.unpainted);
+        for color in [-1, 8, 9, i64::MAX].iter() {
+            assert_eq!(palette.color(Some(*color)), palette.unknown);
+        }
+
+        let empty = Palette {
+            colors: Vec::new(),
+            ..Palette::default()
+        };
+        assert_eq!(empty.color(Some(0)), empty.unknown);
+    }
+
+    #[test]
+    fn svg() {
+        let hull = from_text(SAMPLE).unwrap();
+        let svg = to_svg(&hull, &ImageOptions::default());
+        assert!(
+            svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"24\" height=\"24\"")
+        );
+        assert_eq!(svg.matches("<rect").count(), 8);
+        assert!(svg.contains("<rect x=\"16\" y=\"16\" width=\"8\" height=\"8\" fill=\"#dc2828\"/>"));
+    }
+}
@@ -0,0 +1,70 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn offset(self, d: Point) -> Point {
+        Point {
+            x: self.x + d.x,
+            y: self.y + d.y,
+        }
+    }
+}
+
+/// Unbounded grid of panels. Panels that were never painted read as black (0).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hull {
+    pub panels: HashMap<Point, i64>,
+}
+
+impl Hull {
+    pub fn get(&self, p: Point) -> i64 {
+        *self.panels.get(&p).unwrap_or(&0)
+    }
+
+    pub fn paint(&mut self, p: Point, color: i64) {
+        self.panels.insert(p, color);
+    }
+
+    /// Number of panels painted at least once.
+    pub fn painted(&self) -> usize {
+        self.panels.len()
+    }
+
+    /// Inclusive min and max corner of all panels with the given color.
+    pub fn bounds_of(&self, color: i64) -> Option<(Point, Point)> {
+        self.bounds_where(|c| c == color)
+    }
+
+    /// Inclusive min and max corner of all painted panels.
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        self.bounds_where(|_| true)
+    }
+
+    fn bounds_where(&self, f: impl Fn(i64) -> bool) -> Option<(Point, Point)> {
+        let mut bounds: Option<(Point, Point)> = None;
+        for (p, c) in self.panels.iter() {
+            if !f(*c) {
+                continue;
+            }
+            bounds = Some(match bounds {
+                None => (*p, *p),
+                Some((min, max)) => (
+                    Point {
+                        x: std::cmp::min(min.x, p.x),
+                        y: std::cmp::min(min.y, p.y),
+                    },
+                    Point {
+                        x: std::cmp::max(max.x, p.x),
+                        y: std::cmp::max(max.y, p.y),
+                    },
+                ),
+            });
+        }
+        bounds
+    }
+}
@@ -0,0 +1,182 @@
+use crate::isa::*;
+use crate::parser::parse_intcode;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::rc::Rc;
+
+pub enum ProgramResult {
+    WaitForInputAt,
+    Output(i64),
+    Halted,
+}
+
+/// Host device that answers reads and writes to a mapped address range.
+/// Addresses are passed as offsets relative to the start of the range.
+pub trait Peripheral {
+    fn read(&mut self, offset: u64) -> i64;
+    fn write(&mut self, offset: u64, val: i64);
+}
+
+#[allow(dead_code)]
+/// Peripheral made from a pair of host callbacks.
+pub struct Hooks<R, W> {
+    pub read: R,
+    pub write: W,
+}
+
+impl<R: FnMut(u64) -> i64, W: FnMut(u64, i64)> Peripheral for Hooks<R, W> {
+    fn read(&mut self, offset: u64) -> i64 {
+        (self.read)(offset)
+    }
+    fn write(&mut self, offset: u64, val: i64) {
+        (self.write)(offset, val)
+    }
+}
+
+struct MappedPeripheral {
+    range: Range<u64>,
+    // Reads go through `get(&self)`, so devices with state need interior mutability.
+    device: RefCell<Box<dyn Peripheral>>,
+}
+
+pub struct ProgramState {
+    pub program: HashMap<u64, i64>,
+    pub inputs: VecDeque<i64>,
+    pub pc: u64,
+    pub relative_base: i64,
+    peripherals: Vec<MappedPeripheral>,
+    isa: Rc<InstructionSet>,
+}
+
+impl ProgramState {
+    pub fn new(program: HashMap<u64, i64>) -> ProgramState {
+        ProgramState::with_isa(program, Rc::new(InstructionSet::day09()))
+    }
+
+    pub fn with_isa(program: HashMap<u64, i64>, isa: Rc<InstructionSet>) -> ProgramState {
+        ProgramState {
+            program,
+            inputs: VecDeque::new(),
+            pc: 0,
+            relative_base: 0,
+            peripherals: Vec::new(),
+            isa,
+        }
+    }
+
+    #[allow(dead_code)]
+    /// Routes all reads and writes within `range` to `device` instead of program memory.
+    pub fn map_peripheral(&mut self, range: Range<u64>, device: Box<dyn Peripheral>) {
+        assert!(range.start < range.end, "empty peripheral range");
+        if let Some(other) = self
+            .peripherals
+            .iter()
+            .find(|p| p.range.start < range.end && range.start < p.range.end)
+        {
+            panic!(
+                "peripheral range {:?} overlaps already mapped range {:?}",
+                range, other.range
+            );
+        }
+        self.peripherals.push(MappedPeripheral {
+            range,
+            device: RefCell::new(device),
+        });
+    }
+
+    fn peripheral_at(&self, i: u64) -> Option<&MappedPeripheral> {
+        self.peripherals.iter().find(|p| p.range.contains(&i))
+    }
+
+    pub fn get(&self, i: u64) -> i64 {
+        match self.peripheral_at(i) {
+            Some(p) => p.device.borrow_mut().read(i - p.range.start),
+            None => *self.program.get(&i).unwrap_or(&0),
+        }
+    }
+    pub fn set(&mut self, i: u64, val: i64) {
+        match self.peripheral_at(i) {
+            Some(p) => p.device.borrow_mut().write(i - p.range.start, val),
+            None => {
+                self.program.insert(i, val);
+            }
+        }
+    }
+
+    fn param_mode(&self, p: u64) -> ParamMode {
+        let digit = self.get(self.pc) / (100 * 10i64.pow(p as u32)) % 10;
+        match ParamMode::from_digit(digit) {
+            Some(pmode) if self.isa.allows_mode(pmode) => pmode,
+            _ => panic!(
+                "parameter mode {} not supported by {} at {}",
+                digit, self.isa.name, self.pc
+            ),
+        }
+    }
+
+    fn get_param_address(&self, p: u64) -> u64 {
+        match self.param_mode(p) {
+            ParamMode::Position => self.get(self.pc + p + 1) as u64,
+            ParamMode::Immediate => self.pc + p + 1,
+            ParamMode::Relative => (self.get(self.pc + p + 1) + self.relative_base) as u64,
+        }
+    }
+
+    fn get_param(&self, p: u64) -> i64 {
+        self.get(self.get_param_address(p))
+    }
+
+    pub fn eval_program(&mut self) -> ProgramResult {
+        let isa = Rc::clone(&self.isa);
+        loop {
+            let opcode = self.get(self.pc) % 100;
+            let instruction = match isa.get(opcode) {
+                Some(instruction) => instruction,
+                None => panic!("Invalid opcode {} at {}!", opcode, self.pc),
+            };
+            let operands: Vec<i64> = (0..instruction.arity)
+                .map(|p| {
+                    if instruction.is_write(p) {
+                        if self.param_mode(p as u64) == ParamMode::Immediate {
+                            panic!(
+                                "write operand {} of {} in immediate mode at {}",
+                                p, instruction.name, self.pc
+                            );
+                        }
+                        self.get_param_address(p as u64) as i64
+                    } else {
+                        self.get_param(p as u64)
+                    }
+                })
+                .collect();
+
+            match (instruction.exec)(self, &operands) {
+                Step::Next => self.pc += 1 + instruction.arity as u64,
+                Step::Jump(target) => self.pc = target,
+                Step::Output(output) => {
+                    self.pc += 1 + instruction.arity as u64;
+                    return ProgramResult::Output(output);
+                }
+                Step::WaitForInput => return ProgramResult::WaitForInputAt,
+                Step::Halt => return ProgramResult::Halted,
+            }
+        }
+    }
+}
+
+pub fn load_program(values: &[i64]) -> HashMap<u64, i64> {
+    values
+        .iter()
+        .enumerate()
+        .map(|(i, val)| (i as u64, *val))
+        .collect()
+}
+
+pub fn parse_program(puzzle_input: &str) -> HashMap<u64, i64> {
+    match parse_intcode(puzzle_input) {
+        Ok(values) => load_program(&values),
+        Err(err) => panic!("invalid program: {}", err),
+    }
+}
@@ -0,0 +1,231 @@
+use crate::intcode::ProgramState;
+use std::collections::HashMap;
+use std::fmt;
+
+/// What the VM does after an instruction's semantics ran.
+pub enum Step {
+    Next,
+    Jump(u64),
+    Output(i64),
+    WaitForInput,
+    Halt,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParamMode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl ParamMode {
+    pub fn from_digit(digit: i64) -> Option<ParamMode> {
+        match digit {
+            0 => Some(ParamMode::Position),
+            1 => Some(ParamMode::Immediate),
+            2 => Some(ParamMode::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// Semantics get the operand values; operands listed in `writes` are passed as target addresses instead.
+pub type Semantics = Box<dyn Fn(&mut ProgramState, &[i64]) -> Step>;
+
+pub struct Instruction {
+    pub opcode: i64,
+    pub name: &'static str,
+    pub arity: usize,
+    pub writes: Vec<usize>,
+    pub exec: Semantics,
+}
+
+impl Instruction {
+    pub fn new(
+        opcode: i64,
+        name: &'static str,
+        arity: usize,
+        writes: &[usize],
+        exec: impl Fn(&mut ProgramState, &[i64]) -> Step + 'static,
+    ) -> Instruction {
+        Instruction {
+            opcode,
+            name,
+            arity,
+            writes: writes.to_vec(),
+            exec: Box::new(exec),
+        }
+    }
+
+    pub fn is_write(&self, operand: usize) -> bool {
+        self.writes.contains(&operand)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum IsaError {
+    OpcodeOutOfRange(i64),
+    DuplicateOpcode(i64),
+    WriteOperandOutOfRange { opcode: i64, operand: usize },
+    NoParamModes,
+}
+
+impl fmt::Display for IsaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IsaError::OpcodeOutOfRange(opcode) => {
+                write!(f, "opcode {} does not fit into two digits", opcode)
+            }
+            IsaError::DuplicateOpcode(opcode) => write!(f, "opcode {} defined twice", opcode),
+            IsaError::WriteOperandOutOfRange { opcode, operand } => write!(
+                f,
+                "opcode {} declares write operand {} beyond its arity",
+                opcode, operand
+            ),
+            IsaError::NoParamModes => write!(f, "instruction set allows no parameter modes"),
+        }
+    }
+}
+
+/// An Intcode dialect: the opcodes it understands and the parameter modes it allows.
+pub struct InstructionSet {
+    pub name: &'static str,
+    modes: Vec<ParamMode>,
+    instructions: HashMap<i64, Instruction>,
+}
+
+impl InstructionSet {
+    pub fn new(
+        name: &'static str,
+        modes: &[ParamMode],
+        instructions: Vec<Instruction>,
+    ) -> Result<InstructionSet, IsaError> {
+        if modes.is_empty() {
+            return Err(IsaError::NoParamModes);
+        }
+        let mut isa = InstructionSet {
+            name,
+            modes: modes.to_vec(),
+            instructions: HashMap::new(),
+        };
+        for instruction in instructions {
+            isa = isa.with(instruction)?;
+        }
+        Ok(isa)
+    }
+
+    /// Extends the dialect with another instruction, e.g. an experimental opcode.
+    pub fn with(mut self, instruction: Instruction) -> Result<InstructionSet, IsaError> {
+        if instruction.opcode < 1 || instruction.opcode > 99 {
+            return Err(IsaError::OpcodeOutOfRange(instruction.opcode));
+        }
+        if self.instructions.contains_key(&instruction.opcode) {
+            return Err(IsaError::DuplicateOpcode(instruction.opcode));
+        }
+        if let Some(operand) = instruction.writes.iter().find(|w| **w >= instruction.arity) {
+            return Err(IsaError::WriteOperandOutOfRange {
+                opcode: instruction.opcode,
+                operand: *operand,
+            });
+        }
+        self.instructions.insert(instruction.opcode, instruction);
+        Ok(self)
+    }
+
+    pub fn get(&self, opcode: i64) -> Option<&Instruction> {
+        self.instructions.get(&opcode)
+    }
+
+    pub fn allows_mode(&self, mode: ParamMode) -> bool {
+        self.modes.contains(&mode)
+    }
+
+    /// Day 9: the complete instruction set including the relative base.
+    pub fn day09() -> InstructionSet {
+        let mut instructions = day05_instructions();
+        instructions.push(adjust_relative_base());
+        InstructionSet::new(
+            "day09",
+            &[
+                ParamMode::Position,
+                ParamMode::Immediate,
+                ParamMode::Relative,
+            ],
+            instructions,
+        )
+        .unwrap()
+    }
+}
+
+fn day05_instructions() -> Vec<Instruction> {
+    vec![
+        add(),
+        multiply(),
+        input(),
+        output(),
+        jump_if(true),
+        jump_if(false),
+        compare(7, "lt", |a, b| a < b),
+        compare(8, "eq", |a, b| a == b),
+        halt(),
+    ]
+}
+
+fn add() -> Instruction {
+    Instruction::new(1, "add", 3, &[2], |state, ops| {
+        state.set(ops[2] as u64, ops[0] + ops[1]);
+        Step::Next
+    })
+}
+
+fn multiply() -> Instruction {
+    Instruction::new(2, "mul", 3, &[2], |state, ops| {
+        state.set(ops[2] as u64, ops[0] * ops[1]);
+        Step::Next
+    })
+}
+
+fn input() -> Instruction {
+    Instruction::new(3, "in", 1, &[0], |state, ops| {
+        match state.inputs.pop_front() {
+            Some(val) => {
+                state.set(ops[0] as u64, val);
+                Step::Next
+            }
+            None => Step::WaitForInput,
+        }
+    })
+}
+
+fn output() -> Instruction {
+    Instruction::new(4, "out", 1, &[], |_, ops| Step::Output(ops[0]))
+}
+
+fn jump_if(nonzero: bool) -> Instruction {
+    let (opcode, name) = if nonzero { (5, "jnz") } else { (6, "jz") };
+    Instruction::new(opcode, name, 2, &[], move |_, ops| {
+        if (ops[0] != 0) == nonzero {
+            Step::Jump(ops[1] as u64)
+        } else {
+            Step::Next
+        }
+    })
+}
+
+fn compare(opcode: i64, name: &'static str, cmp: fn(i64, i64) -> bool) -> Instruction {
+    Instruction::new(opcode, name, 3, &[2], move |state, ops| {
+        state.set(ops[2] as u64, if cmp(ops[0], ops[1]) { 1 } else { 0 });
+        Step::Next
+    })
+}
+
+fn adjust_relative_base() -> Instruction {
+    Instruction::new(9, "arb", 1, &[], |state, ops| {
+        state.relative_base += ops[0];
+        Step::Next
+    })
+}
+
+fn halt() -> Instruction {
+    Instruction::new(99, "hlt", 0, &[], |_, _| Step::Halt)
+}
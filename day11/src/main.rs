@@ -1,213 +1,244 @@
-use std::collections::HashSet;
-use std::collections::HashMap;
-use std::collections::VecDeque;
-
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
-struct Point {
-    x: i32,
-    y: i32,
-}
-type Dir = Point;
-
-enum ProgramResult {
-    WaitForInputAt,
-    Output(i64),
-    Halted,
-}
-
-struct ProgramState {
-    program: HashMap<u64, i64>,
-    inputs: VecDeque<i64>,
-    pc: u64,
-    relative_base: i64,
-}
-
-impl ProgramState {
-    fn get(&self, i: u64) -> i64 {
-        *self.program.get(&i).unwrap_or(&0)
-    }
-    fn set(&mut self, i: u64, val: i64) {
-        self.program.insert(i, val);
-    }
-
-    fn get_param_address(&self, p: u64) -> u64 {
-        let pmode = self.get(self.pc) / (100 * 10i64.pow(p as u32)) % 10;
-        match pmode {
-            0 => self.get(self.pc + p + 1) as u64, // position mode
-            1 => self.pc + p + 1,                  // value mode
-            2 => (self.get(self.pc + p + 1) + self.relative_base) as u64, // relative mode
-            _ => panic!("unknown pmode"),
-        }
-    }
-
-    fn get_param(&self, p: u64) -> i64 {
-        self.get(self.get_param_address(p))
-    }
+mod animation;
+mod export;
+mod hull;
+mod intcode;
+mod isa;
+mod ocr;
+mod parser;
+mod robot;
+mod turmite;
+
+use hull::*;
+use robot::*;
+use std::fs::File;
+use std::io::{stdout, BufWriter};
+use std::num::NonZeroUsize;
+use std::path::Path;
+use std::time::Duration;
+
+fn print_panels(hull: &Hull) {
+    let (min, max) = match hull.bounds_of(1) {
+        Some(bounds) => bounds,
+        None => return,
+    };
 
-    fn eval_program(&mut self) -> ProgramResult {
-        loop {
-            let opcode = self.program[&self.pc] % 100;
-            match opcode {
-                1 => self.set(
-                    self.get_param_address(2),
-                    self.get_param(0) + self.get_param(1),
-                ),
-                2 => self.set(
-                    self.get_param_address(2),
-                    self.get_param(0) * self.get_param(1),
-                ),
-                3 => {
-                    let val = match self.inputs.pop_front() {
-                        Some(input) => input,
-                        None => return ProgramResult::WaitForInputAt,
-                    };
-                    self.set(self.get_param_address(0), val);
-                }
-                4 => {
-                    let output = self.get_param(0);
-                    self.pc += 2;
-                    return ProgramResult::Output(output);
-                }
-                5 => {
-                    if self.get_param(0) != 0 {
-                        self.pc = self.get_param(1) as u64;
-                        continue;
-                    }
-                }
-                6 => {
-                    if self.get_param(0) == 0 {
-                        self.pc = self.get_param(1) as u64;
-                        continue;
-                    }
-                }
-                7 => self.set(
-                    self.get_param_address(2),
-                    if self.get_param(0) < self.get_param(1) {
-                        1
-                    } else {
-                        0
-                    },
-                ),
-                8 => self.set(
-                    self.get_param_address(2),
-                    if self.get_param(0) == self.get_param(1) {
-                        1
-                    } else {
-                        0
-                    },
-                ),
-                9 => self.relative_base += self.get_param(0),
-                99 => return ProgramResult::Halted,
-                _ => panic!("Invalid opcode!"),
-            };
-            self.pc += match opcode {
-                1 => 4,
-                2 => 4,
-                3 => 2,
-                4 => 2,
-                5 => 3,
-                6 => 3,
-                7 => 4,
-                8 => 4,
-                9 => 2,
-                _ => panic!("Invalid opcode!"),
+    for y in min.y..(max.y + 1) {
+        for x in min.x..(max.x + 1) {
+            let c = match hull.panels.get(&Point { x, y }) {
+                Some(1) => '█',
+                Some(color) => export::panel_char(*color).unwrap_or('?'),
+                None => ' ',
             };
+            print!("{}", c);
         }
+        println!();
     }
 }
 
-fn parse_program(puzzle_input: &str) -> HashMap<u64, i64> {
-    let mut program = HashMap::new();
-    for (i, instr) in puzzle_input.split(',').enumerate() {
-        program.insert(i as u64, instr.parse::<i64>().unwrap());
+/// Reads the letters painted white. Glyphs like `I` leave their first column dark, so the grid
+/// may start up to `GLYPH_WIDTH - 1` columns left of the first lit panel; the first alignment that
+/// reads every glyph wins.
+fn read_panels(hull: &Hull) -> Result<String, ocr::OcrError> {
+    let (min, max) = hull
+        .bounds_of(1)
+        .unwrap_or((Point { x: 0, y: 0 }, Point { x: -1, y: -1 }));
+    let read_from = |left: i32| {
+        let bitmap = ocr::Bitmap::from_fn(
+            (max.x - left + 1).max(0) as usize,
+            (max.y - min.y + 1) as usize,
+            |x, y| {
+                hull.get(Point {
+                    x: left + x as i32,
+                    y: min.y + y as i32,
+                }) == 1
+            },
+        );
+        ocr::recognize(&bitmap)
+    };
+    let first = read_from(min.x);
+    if first.is_ok() || max.x < min.x {
+        return first;
     }
-    program
+    (1..ocr::GLYPH_WIDTH as i32)
+        .map(|shift| read_from(min.x - shift))
+        .find(|text| text.is_ok())
+        .unwrap_or(first)
 }
 
-fn print_panels(panels: &HashMap<Point, i64>) {
-    let mut minx = 9999;
-    let mut miny = 9999;
-    let mut maxx = -9999;
-    let mut maxy = -9999;
-    for (p,c) in panels {
-        if *c == 1 {
-            minx = std::cmp::min(minx, p.x);
-            miny = std::cmp::min(miny, p.y);
-            maxx = std::cmp::max(maxx, p.x);
-            maxy = std::cmp::max(maxy, p.y);
-        }
-    }
+/// Frame stride of the animation options, every `stride`th step gets drawn.
+fn parse_stride(stride: &str) -> NonZeroUsize {
+    stride
+        .parse()
+        .unwrap_or_else(|_| panic!("stride must be a positive number, got {}", stride))
+}
 
-    for y in miny..(maxy+1) {
-        for x in minx..(maxx+1) {
-            let c = match panels.get(&Point{x:x, y:y}) {
-                Some(0) => ".",
-                Some(1) => "█",
-                None => " ",
-                _ => panic!(),
-            };
-            print!("{}", c);
-        }
-        println!();
+fn run_robot(puzzle_input: &str, start_white: bool) -> History {
+    let mut hull = Hull::default();
+    if start_white {
+        hull.paint(Point { x: 0, y: 0 }, 1);
     }
+    robot::run(&mut IntcodeController::new(puzzle_input), hull)
 }
 
-fn run_robot(puzzle_input: &str, start_white: bool) -> usize {
-    let mut program = ProgramState {
-        program: parse_program(puzzle_input),
-        inputs: VecDeque::new(),
-        pc: 0,
-        relative_base: 0,
+fn main() {
+    let puzzle_input = include_str!("input.txt");
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+    let (start_white, args) = match args.split_first() {
+        Some((&"--part1", rest)) => (false, rest),
+        _ => (true, &args[..]),
     };
 
-    let mut pos = Point{x:0, y:0};
-    let mut dir = 0;
-    let dirs = [Dir{x:0, y:-1}, Dir{x:1, y:0}, Dir{x:0, y:1}, Dir{x:-1, y:0}];
-    let mut panels = HashMap::new();
-    if start_white {
-        panels.insert(pos, 1);
-    }
-    loop {
-        program.inputs.push_back(*panels.get(&pos).unwrap_or(&0));
-
-        let color = match program.eval_program() {
-            ProgramResult::Output(out) => out,
-            ProgramResult::Halted =>{
-                print_panels(&panels);
-                return panels.len();
-            },
-            _ => panic!("invalid program state"),
-        };
-        dir += match program.eval_program() {
-            ProgramResult::Output(out) => out * 2 - 1,
-            _ => panic!("invalid program state"),
-        };
-        if dir < 0 {
-            dir += 4;
-        } else if dir > 3 {
-            dir -= 4;
+    match *args {
+        [] => {
+            println!("part 1 {}", run_robot(puzzle_input, false).hull.painted());
+            println!("part 2");
+            let hull = run_robot(puzzle_input, true).hull;
+            print_panels(&hull);
+            match read_panels(&hull) {
+                Ok(text) => println!("{}", text),
+                Err(error) => println!("{}", error),
+            }
         }
-
-        panels.insert(pos, color);
-
-        pos = Point{x:pos.x+dirs[dir as usize].x, y:pos.y+dirs[dir as usize].y};
+        ["--animate", delay_ms, stride] => {
+            let history = run_robot(puzzle_input, start_white);
+            let delay = Duration::from_millis(delay_ms.parse().unwrap());
+            animation::play_ansi(&history, &mut stdout(), parse_stride(stride), delay).unwrap();
+        }
+        ["--gif", path, scale, stride] => {
+            let history = run_robot(puzzle_input, start_white);
+            let file = BufWriter::new(File::create(path).unwrap());
+            let (scale, stride) = (scale.parse().unwrap(), parse_stride(stride));
+            animation::write_gif(&history, file, scale, stride, Duration::from_millis(40)).unwrap();
+        }
+        ["--ppm", directory, scale, stride] => {
+            let history = run_robot(puzzle_input, start_white);
+            let (scale, stride) = (scale.parse().unwrap(), parse_stride(stride));
+            let count = animation::write_ppm_frames(&history, Path::new(directory), scale, stride);
+            println!("wrote {} frames", count.unwrap());
+        }
+        ["--export", path, scale] => {
+            let hull = run_robot(puzzle_input, start_white).hull;
+            let options = export::ImageOptions {
+                scale: scale.parse().unwrap(),
+                ..export::ImageOptions::default()
+            };
+            match Path::new(path).extension().and_then(|ext| ext.to_str()) {
+                Some("png") => {
+                    let file = BufWriter::new(File::create(path).unwrap());
+                    export::write_png(&hull, &options, file).unwrap();
+                }
+                Some("ppm") => {
+                    let mut file = BufWriter::new(File::create(path).unwrap());
+                    export::write_ppm(&hull, &options, &mut file).unwrap();
+                }
+                Some("svg") => std::fs::write(path, export::to_svg(&hull, &options)).unwrap(),
+                Some("txt") => match export::to_text(&hull) {
+                    Ok(text) => std::fs::write(path, text).unwrap(),
+                    Err(err) => panic!("{}", err),
+                },
+                _ => panic!("unknown image format {}, expected png, ppm, svg or txt", path),
+            }
+        }
+        ["--turmite", rules, steps] | ["--turmite", rules, steps, _, _] => {
+            let rules = turmite::Rules::langton(rules).unwrap_or_else(|e| panic!("{}", e));
+            let intcode = run_robot(puzzle_input, start_white);
+            println!(
+                "intcode robot: {} of {} steps follow {}",
+                turmite::matching_steps(&intcode.steps, &rules),
+                intcode.steps.len(),
+                args[1]
+            );
+            let topology = match args[3..] {
+                [width, height] => {
+                    turmite::Topology::toroidal(width.parse().unwrap(), height.parse().unwrap())
+                        .unwrap_or_else(|| {
+                            panic!("torus size must be positive, got {}x{}", width, height)
+                        })
+                }
+                _ => turmite::Topology::Unbounded,
+            };
+            let ant = turmite::Ant::new(Point { x: 0, y: 0 }, Heading::Up);
+            let mut world = turmite::World::new(rules, topology, vec![ant]);
+            world.hull = intcode.initial_hull;
+            world.run(steps.parse().unwrap());
+            println!("turmite painted {} panels", world.hull.painted());
+            print_panels(&world.hull);
+        }
+        _ => panic!(
+            "usage: day11 [[--part1] --animate delay_ms stride | --gif file scale stride | --ppm directory scale stride | --export file.(png|ppm|svg|txt) scale | --turmite LR-rules steps [torus_width torus_height]]"
+        ),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::export::*;
+    use super::hull::*;
+    use super::robot::*;
+
     #[test]
     fn samples_day11_part1() {
+        let mut controller = ScriptedController::new(&[
+            (1, Turn::Left),
+            (0, Turn::Left),
+            (1, Turn::Left),
+            (1, Turn::Left),
+            (0, Turn::Right),
+            (1, Turn::Left),
+            (1, Turn::Left),
+        ]);
+        let history = run(&mut controller, Hull::default());
+        assert_eq!(history.hull.painted(), 6);
+        assert_eq!(
+            to_text(&history.hull),
+            Ok("origin -1 -1\n  #\n..#\n##\n".to_string())
+        );
+        assert_eq!(history.position, Point { x: 0, y: -1 });
+        assert_eq!(history.heading, Heading::Left);
+        assert_eq!(history.steps.len(), 7);
+        assert_eq!(
+            history.steps[4],
+            RobotStep {
+                position: Point { x: 0, y: 0 },
+                heading: Heading::Up,
+                color_seen: 1,
+                paint: 0,
+                turn: Turn::Right,
+            }
+        );
     }
 
     #[test]
-    fn samples_day11_part2() {}
-}
-
-fn main() {
-    let puzzle_input = include_str!("input.txt");
-    println!("part 1 {}", run_robot(puzzle_input, false));
+    fn samples_day11_part2() {
+        let hull = super::run_robot(include_str!("input.txt"), true).hull;
+        assert_eq!(super::read_panels(&hull), Ok("PKFPAZRP".to_string()));
+    }
 
-    println!("part 2");
-    run_robot(puzzle_input, true);
+    #[test]
+    fn read_leading_i() {
+        let rows = [
+            ".###.#...",
+            "..#..#...",
+            "..#..#...",
+            "..#..#...",
+            "..#..#...",
+            ".###.####",
+        ];
+        let mut hull = Hull::default();
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    hull.paint(
+                        Point {
+                            x: x as i32,
+                            y: y as i32,
+                        },
+                        1,
+                    );
+                }
+            }
+        }
+        assert_eq!(super::read_panels(&hull), Ok("IL".to_string()));
+    }
 }
@@ -0,0 +1,120 @@
+use std::fmt;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    InvalidNumber,
+    MissingValue,
+    ExpectedComma,
+}
+
+/// Position and text of the token a program failed to parse at; lines and columns start at 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let what = match self.kind {
+            ParseErrorKind::InvalidNumber => "invalid number",
+            ParseErrorKind::MissingValue => "missing value before",
+            ParseErrorKind::ExpectedComma => "expected ',' before",
+        };
+        write!(
+            f,
+            "line {}, column {}: {} `{}`",
+            self.line, self.column, what, self.token
+        )
+    }
+}
+
+struct Token {
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+impl Token {
+    fn parse(self) -> Result<i64, ParseError> {
+        self.text.parse::<i64>().map_err(|_| ParseError {
+            kind: ParseErrorKind::InvalidNumber,
+            line: self.line,
+            column: self.column,
+            token: self.text,
+        })
+    }
+}
+
+/// Parses comma separated Intcode. Whitespace (including newlines) may appear around values
+/// and `#` starts a comment that runs until the end of the line.
+pub fn parse_intcode(text: &str) -> Result<Vec<i64>, ParseError> {
+    let mut values = Vec::new();
+    let mut token: Option<Token> = None;
+    let mut token_ended = false;
+    let mut last_comma = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        let code = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => line,
+        };
+        for (column_idx, (byte_idx, c)) in code.char_indices().enumerate() {
+            let (line, column) = (line_idx + 1, column_idx + 1);
+            if c == ',' {
+                match token.take() {
+                    Some(t) => values.push(t.parse()?),
+                    None => {
+                        return Err(ParseError {
+                            kind: ParseErrorKind::MissingValue,
+                            line,
+                            column,
+                            token: ",".to_string(),
+                        })
+                    }
+                }
+                token_ended = false;
+                last_comma = Some((line, column));
+            } else if c.is_whitespace() {
+                token_ended = token.is_some();
+            } else if token_ended {
+                return Err(ParseError {
+                    kind: ParseErrorKind::ExpectedComma,
+                    line,
+                    column,
+                    token: code[byte_idx..]
+                        .split(|c: char| c == ',' || c.is_whitespace())
+                        .next()
+                        .unwrap()
+                        .to_string(),
+                });
+            } else {
+                token
+                    .get_or_insert(Token {
+                        text: String::new(),
+                        line,
+                        column,
+                    })
+                    .text
+                    .push(c);
+            }
+        }
+        token_ended = token.is_some();
+    }
+
+    match (token, last_comma) {
+        (Some(t), _) => values.push(t.parse()?),
+        (None, Some((line, column))) => {
+            return Err(ParseError {
+                kind: ParseErrorKind::MissingValue,
+                line,
+                column: column + 1,
+                token: "end of input".to_string(),
+            })
+        }
+        (None, None) => {}
+    }
+    Ok(values)
+}
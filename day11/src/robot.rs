@@ -0,0 +1,201 @@
+use crate::hull::*;
+use crate::intcode::*;
+use std::collections::VecDeque;
+
+/// Offsets for the headings in clockwise order, starting with up (y grows downwards).
+pub const DIRS: [Point; 4] = [
+    Point { x: 0, y: -1 },
+    Point { x: 1, y: 0 },
+    Point { x: 0, y: 1 },
+    Point { x: -1, y: 0 },
+];
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Heading {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Heading {
+    pub fn from_index(index: i64) -> Heading {
+        match index.rem_euclid(4) {
+            0 => Heading::Up,
+            1 => Heading::Right,
+            2 => Heading::Down,
+            _ => Heading::Left,
+        }
+    }
+
+    /// Index into `DIRS`.
+    pub fn index(self) -> i64 {
+        self as i64
+    }
+
+    pub fn offset(self) -> Point {
+        DIRS[self as usize]
+    }
+
+    pub fn turn(self, turn: Turn) -> Heading {
+        Heading::from_index(self.index() + turn.quarter_turns())
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Turn {
+    Left,
+    Right,
+    /// Keep the current heading.
+    None,
+    /// Turn around.
+    Back,
+}
+
+impl Turn {
+    /// Clockwise quarter turns.
+    pub fn quarter_turns(self) -> i64 {
+        match self {
+            Turn::Left => -1,
+            Turn::Right => 1,
+            Turn::None => 0,
+            Turn::Back => 2,
+        }
+    }
+}
+
+/// Decides what the robot does on each panel.
+pub trait Controller {
+    /// Gets the color of the panel below the robot and returns the color to paint it
+    /// and where to turn afterwards, or `None` once the controller is done.
+    fn step(&mut self, color: i64) -> Option<(i64, Turn)>;
+}
+
+impl<F: FnMut(i64) -> Option<(i64, Turn)>> Controller for F {
+    fn step(&mut self, color: i64) -> Option<(i64, Turn)> {
+        self(color)
+    }
+}
+
+/// The puzzle's robot brain: reads the camera as input and outputs a color followed by a turn (0 left, 1 right).
+pub struct IntcodeController {
+    pub program: ProgramState,
+}
+
+impl IntcodeController {
+    pub fn new(puzzle_input: &str) -> IntcodeController {
+        IntcodeController {
+            program: ProgramState::new(parse_program(puzzle_input)),
+        }
+    }
+}
+
+impl Controller for IntcodeController {
+    fn step(&mut self, color: i64) -> Option<(i64, Turn)> {
+        self.program.inputs.push_back(color);
+        let paint = match self.program.eval_program() {
+            ProgramResult::Output(out) => out,
+            ProgramResult::Halted => return None,
+            _ => panic!("invalid program state"),
+        };
+        let turn = match self.program.eval_program() {
+            ProgramResult::Output(0) => Turn::Left,
+            ProgramResult::Output(1) => Turn::Right,
+            ProgramResult::Output(out) => panic!("invalid turn {}", out),
+            _ => panic!("invalid program state"),
+        };
+        Some((paint, turn))
+    }
+}
+
+/// Replays a fixed list of moves regardless of what the camera sees.
+#[allow(dead_code)]
+pub struct ScriptedController {
+    pub moves: VecDeque<(i64, Turn)>,
+}
+
+impl ScriptedController {
+    #[allow(dead_code)]
+    pub fn new(moves: &[(i64, Turn)]) -> ScriptedController {
+        ScriptedController {
+            moves: moves.iter().copied().collect(),
+        }
+    }
+}
+
+impl Controller for ScriptedController {
+    fn step(&mut self, _color: i64) -> Option<(i64, Turn)> {
+        self.moves.pop_front()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RobotStep {
+    pub position: Point,
+    pub heading: Heading,
+    pub color_seen: i64,
+    pub paint: i64,
+    pub turn: Turn,
+}
+
+pub struct History {
+    /// Hull before the first step.
+    pub initial_hull: Hull,
+    pub steps: Vec<RobotStep>,
+    pub hull: Hull,
+    pub position: Point,
+    pub heading: Heading,
+}
+
+/// Lets `controller` paint `hull`, starting at the origin facing up, until it stops.
+pub fn run(controller: &mut dyn Controller, hull: Hull) -> History {
+    let mut history = History {
+        initial_hull: hull.clone(),
+        steps: Vec::new(),
+        hull,
+        position: Point { x: 0, y: 0 },
+        heading: Heading::Up,
+    };
+    loop {
+        let color_seen = history.hull.get(history.position);
+        let (paint, turn) = match controller.step(color_seen) {
+            Some(action) => action,
+            None => return history,
+        };
+        history.steps.push(RobotStep {
+            position: history.position,
+            heading: history.heading,
+            color_seen,
+            paint,
+            turn,
+        });
+        history.hull.paint(history.position, paint);
+        history.heading = history.heading.turn(turn);
+        history.position = history.position.offset(history.heading.offset());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closure_controller() {
+        let mut remaining = 4;
+        let mut controller = |_| {
+            remaining -= 1;
+            if remaining < 0 {
+                None
+            } else {
+                Some((1, Turn::Right))
+            }
+        };
+        let history = run(&mut controller, Hull::default());
+        assert_eq!(history.hull.painted(), 4);
+        assert_eq!(
+            history.hull.bounds_of(1),
+            Some((Point { x: 0, y: 0 }, Point { x: 1, y: 1 }))
+        );
+        assert_eq!(history.position, Point { x: 0, y: 0 });
+    }
+}
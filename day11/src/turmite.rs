@@ -0,0 +1,390 @@
+use crate::hull::*;
+use crate::robot::*;
+use std::fmt;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub paint: i64,
+    pub turn: Turn,
+    pub next_state: usize,
+}
+
+/// Transition table of a turmite: for every state and panel color what to paint,
+/// where to turn and which state to continue in. Colors run from 0 to `colors - 1`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub colors: usize,
+    table: Vec<Rule>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RuleError {
+    /// Langton notation only knows `L`, `R`, `N` (no turn) and `U` (u-turn).
+    InvalidTurn(char),
+    TooFewColors,
+    /// Every state needs one rule per color.
+    RaggedTable {
+        state: usize,
+    },
+    ColorOutOfRange {
+        state: usize,
+        color: usize,
+    },
+    StateOutOfRange {
+        state: usize,
+        color: usize,
+    },
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuleError::InvalidTurn(c) => write!(f, "invalid turn `{}`", c),
+            RuleError::TooFewColors => write!(f, "a turmite needs at least two colors"),
+            RuleError::RaggedTable { state } => {
+                write!(f, "state {} has a different number of colors", state)
+            }
+            RuleError::ColorOutOfRange { state, color } => {
+                write!(f, "state {}, color {}: paint out of range", state, color)
+            }
+            RuleError::StateOutOfRange { state, color } => {
+                write!(
+                    f,
+                    "state {}, color {}: next state out of range",
+                    state, color
+                )
+            }
+        }
+    }
+}
+
+impl Rules {
+    /// `table[state][color]`, validated for consistent colors and states.
+    pub fn new(table: Vec<Vec<Rule>>) -> Result<Rules, RuleError> {
+        let colors = table.first().map_or(0, |rules| rules.len());
+        if colors < 2 {
+            return Err(RuleError::TooFewColors);
+        }
+        for (state, rules) in table.iter().enumerate() {
+            if rules.len() != colors {
+                return Err(RuleError::RaggedTable { state });
+            }
+            for (color, rule) in rules.iter().enumerate() {
+                if rule.paint < 0 || rule.paint as usize >= colors {
+                    return Err(RuleError::ColorOutOfRange { state, color });
+                }
+                if rule.next_state >= table.len() {
+                    return Err(RuleError::StateOutOfRange { state, color });
+                }
+            }
+        }
+        Ok(Rules {
+            colors,
+            table: table.into_iter().flatten().collect(),
+        })
+    }
+
+    /// Single state ant in Langton notation, e.g. `RL` for Langton's ant:
+    /// on color `i` turn as given by the `i`th letter and paint the next color.
+    pub fn langton(turns: &str) -> Result<Rules, RuleError> {
+        let colors = turns.chars().count();
+        let rules = turns
+            .chars()
+            .enumerate()
+            .map(|(color, c)| {
+                let turn = match c {
+                    'L' => Turn::Left,
+                    'R' => Turn::Right,
+                    'N' => Turn::None,
+                    'U' => Turn::Back,
+                    _ => return Err(RuleError::InvalidTurn(c)),
+                };
+                Ok(Rule {
+                    paint: ((color + 1) % colors) as i64,
+                    turn,
+                    next_state: 0,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Rules::new(vec![rules])
+    }
+
+    #[allow(dead_code)]
+    pub fn states(&self) -> usize {
+        self.table.len() / self.colors
+    }
+
+    /// Colors outside the table (e.g. painted by someone else) wrap around.
+    pub fn get(&self, state: usize, color: i64) -> Rule {
+        self.table[state * self.colors + color.rem_euclid(self.colors as i64) as usize]
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Topology {
+    Unbounded,
+    /// Inclusive area; ants that walk off the edge stop.
+    Bounded {
+        min: Point,
+        max: Point,
+    },
+    /// `width`×`height` panels starting at the origin, edges wrap around.
+    Toroidal {
+        width: i32,
+        height: i32,
+    },
+}
+
+impl Topology {
+    /// A torus needs at least one panel each way, `None` otherwise.
+    pub fn toroidal(width: i32, height: i32) -> Option<Topology> {
+        if width > 0 && height > 0 {
+            Some(Topology::Toroidal { width, height })
+        } else {
+            None
+        }
+    }
+
+    /// Where a move from `from` into `direction` ends up, `None` if it leaves the world.
+    fn advance(self, from: Point, heading: Heading) -> Option<Point> {
+        let to = from.offset(heading.offset());
+        match self {
+            Topology::Unbounded => Some(to),
+            Topology::Bounded { min, max } => {
+                if to.x < min.x || to.y < min.y || to.x > max.x || to.y > max.y {
+                    None
+                } else {
+                    Some(to)
+                }
+            }
+            Topology::Toroidal { width, height } => Some(Point {
+                x: to.x.rem_euclid(width),
+                y: to.y.rem_euclid(height),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Ant {
+    pub position: Point,
+    pub heading: Heading,
+    pub state: usize,
+    /// Cleared once the ant walked off a bounded world.
+    pub active: bool,
+}
+
+impl Ant {
+    pub fn new(position: Point, heading: Heading) -> Ant {
+        Ant {
+            position,
+            heading,
+            state: 0,
+            active: true,
+        }
+    }
+}
+
+pub struct World {
+    pub rules: Rules,
+    pub topology: Topology,
+    pub hull: Hull,
+    pub ants: Vec<Ant>,
+    pub steps: usize,
+}
+
+impl World {
+    pub fn new(rules: Rules, topology: Topology, ants: Vec<Ant>) -> World {
+        World {
+            rules,
+            topology,
+            hull: Hull::default(),
+            ants,
+            steps: 0,
+        }
+    }
+
+    /// Moves every active ant once, in order, so later ants see what earlier ones painted.
+    /// Returns the number of ants that are still active.
+    pub fn step(&mut self) -> usize {
+        for ant in self.ants.iter_mut().filter(|ant| ant.active) {
+            let rule = self.rules.get(ant.state, self.hull.get(ant.position));
+            self.hull.paint(ant.position, rule.paint);
+            ant.heading = ant.heading.turn(rule.turn);
+            ant.state = rule.next_state;
+            match self.topology.advance(ant.position, ant.heading) {
+                Some(position) => ant.position = position,
+                None => ant.active = false,
+            }
+        }
+        self.steps += 1;
+        self.ants.iter().filter(|ant| ant.active).count()
+    }
+
+    /// Steps `count` times or until no ant is active.
+    pub fn run(&mut self, count: usize) {
+        for _ in 0..count {
+            if self.step() == 0 {
+                break;
+            }
+        }
+    }
+}
+
+/// Drives the day11 robot with a turmite rule table for a limited number of steps,
+/// so classic rule sets can be compared with the Intcode controller.
+#[allow(dead_code)]
+pub struct TurmiteController {
+    pub rules: Rules,
+    pub state: usize,
+    pub remaining: usize,
+}
+
+impl TurmiteController {
+    #[allow(dead_code)]
+    pub fn new(rules: Rules, steps: usize) -> TurmiteController {
+        TurmiteController {
+            rules,
+            state: 0,
+            remaining: steps,
+        }
+    }
+}
+
+impl Controller for TurmiteController {
+    fn step(&mut self, color: i64) -> Option<(i64, Turn)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let rule = self.rules.get(self.state, color);
+        self.state = rule.next_state;
+        Some((rule.paint, rule.turn))
+    }
+}
+
+/// Number of robot steps that did what `rules` would have done on the same panel,
+/// following the rule table's state through the steps.
+pub fn matching_steps(steps: &[RobotStep], rules: &Rules) -> usize {
+    let mut state = 0;
+    let mut matching = 0;
+    for step in steps {
+        let rule = rules.get(state, step.color_seen);
+        if rule.paint == step.paint && rule.turn == step.turn {
+            matching += 1;
+        }
+        state = rule.next_state;
+    }
+    matching
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn origin() -> Point {
+        Point { x: 0, y: 0 }
+    }
+
+    #[test]
+    fn langton_square() {
+        let mut world = World::new(
+            Rules::langton("RL").unwrap(),
+            Topology::Unbounded,
+            vec![Ant::new(origin(), Heading::Up)],
+        );
+        world.run(4);
+        assert_eq!(world.hull.painted(), 4);
+        assert_eq!(
+            world.hull.bounds_of(1),
+            Some((origin(), Point { x: 1, y: 1 }))
+        );
+        assert_eq!(world.ants[0], Ant::new(origin(), Heading::Up));
+    }
+
+    #[test]
+    fn controller_matches_world() {
+        let rules = Rules::langton("RLR").unwrap();
+        let mut world = World::new(
+            rules.clone(),
+            Topology::Unbounded,
+            vec![Ant::new(origin(), Heading::Up)],
+        );
+        world.run(500);
+        let history = run(&mut TurmiteController::new(rules, 500), Hull::default());
+        assert_eq!(history.steps.len(), 500);
+        assert_eq!(history.hull, world.hull);
+        assert_eq!(history.position, world.ants[0].position);
+        assert_eq!(history.heading, world.ants[0].heading);
+        assert_eq!(matching_steps(&history.steps, &world.rules), 500);
+        assert!(matching_steps(&history.steps, &Rules::langton("RL").unwrap()) < 500);
+    }
+
+    #[test]
+    fn topologies() {
+        let rules = Rules::langton("RL").unwrap();
+        let mut bounded = World::new(
+            rules.clone(),
+            Topology::Bounded {
+                min: origin(),
+                max: Point { x: 1, y: 1 },
+            },
+            vec![Ant::new(origin(), Heading::Up)],
+        );
+        bounded.run(100);
+        // The fifth step turns left off the square.
+        assert_eq!(bounded.steps, 5);
+        assert!(!bounded.ants[0].active);
+
+        assert_eq!(Topology::toroidal(0, 0), None);
+        assert_eq!(Topology::toroidal(3, -2), None);
+        let mut torus = World::new(
+            rules,
+            Topology::toroidal(3, 2).unwrap(),
+            vec![
+                Ant::new(origin(), Heading::Left),
+                Ant::new(Point { x: 0, y: 1 }, Heading::Down),
+            ],
+        );
+        torus.run(1);
+        assert_eq!(torus.ants[0].position, Point { x: 0, y: 1 });
+        assert_eq!(torus.ants[1].position, Point { x: 2, y: 1 });
+        // The second ant sees the panel the first one just painted.
+        assert_eq!(torus.hull.get(Point { x: 0, y: 1 }), 1);
+        torus.run(1000);
+        assert!(torus
+            .hull
+            .panels
+            .keys()
+            .all(|p| p.x >= 0 && p.x < 3 && p.y >= 0 && p.y < 2));
+    }
+
+    #[test]
+    fn multi_state_rules() {
+        let rule = |paint, turn, next_state| Rule {
+            paint,
+            turn,
+            next_state,
+        };
+        // Fibonacci turmite.
+        let rules = Rules::new(vec![
+            vec![rule(1, Turn::Left, 1), rule(1, Turn::Left, 1)],
+            vec![rule(1, Turn::Right, 1), rule(0, Turn::None, 0)],
+        ])
+        .unwrap();
+        assert_eq!(rules.states(), 2);
+        assert_eq!(rules.get(1, 3), rule(0, Turn::None, 0));
+
+        assert_eq!(Rules::langton("RX"), Err(RuleError::InvalidTurn('X')));
+        assert_eq!(Rules::langton("R"), Err(RuleError::TooFewColors));
+        assert_eq!(
+            Rules::new(vec![vec![rule(2, Turn::Left, 0), rule(0, Turn::Left, 0)]]),
+            Err(RuleError::ColorOutOfRange { state: 0, color: 0 })
+        );
+        assert_eq!(
+            Rules::new(vec![vec![rule(1, Turn::Left, 0), rule(0, Turn::Left, 1)]]),
+            Err(RuleError::StateOutOfRange { state: 0, color: 1 })
+        );
+    }
+}